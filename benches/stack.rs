@@ -0,0 +1,71 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+const WIDTH: usize = 200;
+const HEIGHT: usize = 60;
+
+/// A full screen of cells, cycling through every char in the Box Drawing
+/// block plus spaces and some text, starting at `offset`.
+fn screen(offset: usize) -> Vec<char> {
+    let chars: Vec<char> = ('\u{2500}'..='\u{257f}').chain(" abc".chars()).collect();
+
+    (0..WIDTH * HEIGHT)
+        .map(|i| chars[(i * 7 + offset) % chars.len()])
+        .collect()
+}
+
+fn char_to_bits(c: &mut Criterion) {
+    let cells = screen(0);
+
+    c.bench_function("char_to_bits full screen", |b| {
+        b.iter(|| {
+            for &cell in &cells {
+                black_box(unicode_line_stacker::char_to_bits(black_box(cell)));
+            }
+        })
+    });
+}
+
+fn bits_to_char(c: &mut Criterion) {
+    let bits: Vec<u32> = screen(0)
+        .into_iter()
+        .filter_map(unicode_line_stacker::char_to_bits)
+        .map(|bits| bits as u32)
+        .collect();
+
+    c.bench_function("bits_to_char full screen", |b| {
+        b.iter(|| {
+            for &bits in &bits {
+                black_box(unicode_line_stacker::bits_to_char(black_box(bits)));
+            }
+        })
+    });
+}
+
+fn stack(c: &mut Criterion) {
+    let bottom = screen(0);
+    let top = screen(13);
+
+    c.bench_function("stack full screen", |b| {
+        b.iter(|| {
+            for (&a, &b) in bottom.iter().zip(&top) {
+                black_box(unicode_line_stacker::stack(black_box(a), black_box(b)));
+            }
+        })
+    });
+}
+
+fn stack_slices(c: &mut Criterion) {
+    let bottom = screen(0);
+    let top = screen(13);
+
+    c.bench_function("stack_slices full screen", |b| {
+        b.iter_batched_ref(
+            || bottom.clone(),
+            |dst| unicode_line_stacker::stack_slices(dst, black_box(&top)),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(benches, char_to_bits, bits_to_char, stack, stack_slices);
+criterion_main!(benches);
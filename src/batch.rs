@@ -0,0 +1,202 @@
+//! Stacking whole rows of cells, or whole diagrams, at once.
+
+use crate::{stack_with, Canvas, StackError, StackPolicy};
+
+/// Stack each char of `src` on top of the corresponding char of `dst`,
+/// storing the results in `dst`.
+///
+/// Cells that cannot be stacked, because one of them is not a line-drawing
+/// char or the two cannot be drawn together, take the char from `src`, as
+/// with [`StackPolicy::TopWins`].  A space in `src` is transparent, so it
+/// always leaves `dst` unchanged.
+///
+/// # Examples
+///
+/// ```
+/// let mut dst: Vec<char> = "─a──b".chars().collect();
+/// let src: Vec<char> = "│ ┴x ".chars().collect();
+///
+/// unicode_line_stacker::stack_slices(&mut dst, &src);
+///
+/// assert_eq!("┼a┴xb", dst.iter().collect::<String>());
+/// ```
+///
+/// # Panics
+///
+/// Panics if `dst` and `src` have different lengths.
+pub fn stack_slices(dst: &mut [char], src: &[char]) {
+    assert_eq!(dst.len(), src.len(), "Slices must have the same length");
+
+    for (bottom, &top) in dst.iter_mut().zip(src) {
+        *bottom = stack_cell(*bottom, top);
+    }
+}
+
+/// Stack the row `top` on top of the row `bottom` and return the result.
+///
+/// Each pair of chars is stacked as in [`stack_slices`].
+///
+/// # Examples
+///
+/// ```
+/// let result = unicode_line_stacker::stack_str("┌──┐", "╷ ok");
+/// assert_eq!("┌─ok", result);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `bottom` and `top` have different numbers of chars.
+pub fn stack_str(bottom: &str, top: &str) -> String {
+    let mut bottom_chars = bottom.chars();
+    let mut top_chars = top.chars();
+    let mut result = String::with_capacity(bottom.len().max(top.len()));
+
+    loop {
+        match (bottom_chars.next(), top_chars.next()) {
+            (Some(b), Some(t)) => result.push(stack_cell(b, t)),
+            (None, None) => return result,
+            _ => panic!("Rows must have the same number of chars"),
+        }
+    }
+}
+
+/// Stack the diagram `top` on top of the diagram `base`, with the top left
+/// corner of `top` at column `dx` and row `dy` of `base`, and return the
+/// result.
+///
+/// Each pair of chars is stacked as in [`stack_slices`], so spaces in `top`
+/// are transparent.  The result grows to fit `top`, and its rows are padded
+/// with spaces to the same width.
+///
+/// # Examples
+///
+/// ```
+/// let base = "┌──┐\n│  │\n└──┘";
+/// let top = "┌──┐\n│ab│\n└──┘";
+///
+/// let result = unicode_line_stacker::overlay(base, top, 2, 1);
+///
+/// assert_eq!("┌──┐  \n│ ┌┼─┐\n└─┼ab│\n  └──┘", result);
+/// ```
+pub fn overlay(base: &str, top: &str, dx: usize, dy: usize) -> String {
+    overlay_with(base, top, dx, dy, StackPolicy::TopWins)
+        .expect("stacking with StackPolicy::TopWins always succeeds")
+}
+
+/// Like [`overlay`], but with `policy` deciding what happens when two cells
+/// cannot be stacked.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::{overlay_with, Operand, StackError, StackPolicy};
+///
+/// let result = overlay_with("a─", "─b", 0, 0, StackPolicy::LineWins);
+/// assert_eq!(Ok("──".to_string()), result);
+///
+/// let result = overlay_with("a─", "b", 0, 0, StackPolicy::Error);
+/// assert_eq!(Err(StackError::UnsupportedOperand('a', Operand::First)), result);
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if any pair of cells cannot be stacked under `policy`.
+pub fn overlay_with(
+    base: &str,
+    top: &str,
+    dx: usize,
+    dy: usize,
+    policy: StackPolicy,
+) -> Result<String, StackError> {
+    let top = Canvas::from(top);
+    let mut result = Canvas::from(base);
+    result.resize(
+        result.width().max(dx + top.width()),
+        result.height().max(dy + top.height()),
+    );
+
+    for (y, row) in top.rows().enumerate() {
+        for (x, &c) in row.iter().enumerate() {
+            let (x, y) = (x + dx, y + dy);
+            let bottom = result.get(x, y).expect("result was grown to fit top");
+            result.set(x, y, stack_with(bottom, c, policy)?);
+        }
+    }
+
+    Ok(result.to_string())
+}
+
+#[inline]
+fn stack_cell(bottom: char, top: char) -> char {
+    stack_with(bottom, top, StackPolicy::TopWins).unwrap_or(top)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn space_is_transparent_over_text() {
+        let mut dst = ['a', '\u{2500}'];
+
+        stack_slices(&mut dst, &[' ', ' ']);
+
+        assert_eq!(['a', '\u{2500}'], dst);
+    }
+
+    #[test]
+    fn space_keeps_rounded_corner() {
+        let mut dst = ['\u{256d}', '\u{2500}'];
+
+        stack_slices(&mut dst, &[' ', ' ']);
+
+        assert_eq!(['\u{256d}', '\u{2500}'], dst);
+    }
+
+    #[test]
+    fn incompatible_lines_take_top() {
+        assert_eq!("\u{2571}", stack_str("\u{2500}", "\u{2571}"));
+    }
+
+    #[test]
+    fn overlay_grows_past_base() {
+        assert_eq!(
+            "\u{2500}   \n   \u{2502}",
+            overlay("\u{2500}", "\u{2502}", 3, 1)
+        );
+    }
+
+    #[test]
+    fn overlay_onto_empty_base() {
+        assert_eq!(" x", overlay("", "x", 1, 0));
+        assert_eq!("\u{2500}", overlay("\u{2500}", "", 0, 0));
+    }
+
+    #[test]
+    fn overlay_keeps_rounded_corners() {
+        let panel = "\u{256d}\u{2500}\u{256e}\n\u{2570}\u{2500}\u{256f}";
+
+        assert_eq!(panel, overlay("", panel, 0, 0));
+        assert_eq!(panel, overlay(panel, "   \n   ", 0, 0));
+    }
+
+    #[test]
+    fn overlay_text_wins() {
+        assert_eq!(
+            Ok("a\u{253c}".to_string()),
+            overlay_with("a\u{2500}", "\u{2500}\u{2502}", 0, 0, StackPolicy::TextWins)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "same length")]
+    fn stack_slices_panics_on_length_mismatch() {
+        stack_slices(&mut [' '], &[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "same number of chars")]
+    fn stack_str_panics_on_length_mismatch() {
+        stack_str("\u{2500}\u{2500}", "\u{2502}");
+    }
+}
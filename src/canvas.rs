@@ -0,0 +1,590 @@
+//! The [`Canvas`] grid type.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::{orthogonal_lines, stack_with, Directions, Lines, StackPolicy, Weight};
+
+/// The arms to draw in each cell of a shape.
+type Arms = HashMap<(usize, usize), Directions>;
+
+/// A fixed-size 2-D grid of chars that line-drawing chars can be stacked
+/// onto.
+///
+/// Coordinates are `(x, y)`, with `(0, 0)` in the top left corner.  Writes
+/// outside the canvas are clipped, meaning they are silently ignored.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::Canvas;
+///
+/// let mut canvas = Canvas::new(3, 2);
+/// canvas.draw(0, 0, '┌');
+/// canvas.draw(1, 0, '─');
+/// canvas.draw(1, 0, '│');
+/// canvas.draw(2, 1, '┴');
+/// canvas.draw(5, 5, '┼'); // clipped
+///
+/// assert_eq!(Some('┼'), canvas.get(1, 0));
+/// assert_eq!("┌┼ \n  ┴", canvas.to_string());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    cells: Vec<char>,
+}
+
+impl Canvas {
+    /// Create a canvas filled with spaces.
+    pub fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width,
+            height,
+            cells: vec![' '; width * height],
+        }
+    }
+
+    /// Return the width of the canvas.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Return the height of the canvas.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Return the char at `(x, y)`, or `None` if it is outside the canvas.
+    #[inline]
+    pub fn get(&self, x: usize, y: usize) -> Option<char> {
+        self.index(x, y).map(|i| self.cells[i])
+    }
+
+    /// Replace the char at `(x, y)` with `ch`.
+    #[inline]
+    pub fn set(&mut self, x: usize, y: usize, ch: char) {
+        if let Some(i) = self.index(x, y) {
+            self.cells[i] = ch;
+        }
+    }
+
+    /// Stack `ch` on top of the char at `(x, y)`.
+    ///
+    /// If the two cannot be stacked, because one of them is not a
+    /// line-drawing char or the two cannot be drawn together, `ch` replaces
+    /// the char underneath, as with [`StackPolicy::TopWins`].  Drawing a space
+    /// leaves the canvas unchanged.
+    #[inline]
+    pub fn draw(&mut self, x: usize, y: usize, ch: char) {
+        if let Some(i) = self.index(x, y) {
+            let cell = &mut self.cells[i];
+            *cell = stack_with(*cell, ch, StackPolicy::TopWins).unwrap_or(ch);
+        }
+    }
+
+    /// Draw a horizontal line in row `y` from column `x0` to column `x1`,
+    /// inclusive.
+    ///
+    /// The end cells get half-length stubs, so the line ends exactly at its
+    /// endpoints, and each cell is stacked onto what is already there.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unicode_line_stacker::{Canvas, Weight};
+    ///
+    /// let mut canvas = Canvas::new(5, 1);
+    /// canvas.hline(0, 4, 0, Weight::Light);
+    /// assert_eq!("╶───╴", canvas.to_string());
+    /// ```
+    pub fn hline(&mut self, x0: usize, x1: usize, y: usize, weight: Weight) {
+        let mut arms = Arms::new();
+        self.hline_arms(&mut arms, x0, x1, y);
+        self.draw_arms(arms, weight);
+    }
+
+    /// Draw a vertical line in column `x` from row `y0` to row `y1`,
+    /// inclusive.
+    ///
+    /// The end cells get half-length stubs, as with [`hline`](Canvas::hline).
+    pub fn vline(&mut self, x: usize, y0: usize, y1: usize, weight: Weight) {
+        let mut arms = Arms::new();
+        self.vline_arms(&mut arms, x, y0, y1);
+        self.draw_arms(arms, weight);
+    }
+
+    /// Draw the outline of a rectangle with its top left corner at `(x, y)`.
+    ///
+    /// The outline is stacked onto what is already there, so rectangles that
+    /// overlap or share edges join up.  Rectangles one cell wide or high are
+    /// drawn as a single line, and a rectangle of a single cell draws
+    /// nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unicode_line_stacker::{Canvas, Weight};
+    ///
+    /// let mut canvas = Canvas::new(5, 3);
+    /// canvas.rect(0, 0, 3, 3, Weight::Light);
+    /// canvas.rect(2, 0, 3, 3, Weight::Light);
+    ///
+    /// assert_eq!("┌─┬─┐\n│ │ │\n└─┴─┘", canvas.to_string());
+    ///
+    /// let mut canvas = Canvas::new(3, 2);
+    /// canvas.rect(0, 0, 3, 2, Weight::Double);
+    /// assert_eq!("╔═╗\n╚═╝", canvas.to_string());
+    /// ```
+    pub fn rect(&mut self, x: usize, y: usize, width: usize, height: usize, weight: Weight) {
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let right = x.saturating_add(width - 1);
+        let bottom = y.saturating_add(height - 1);
+        let mut arms = Arms::new();
+        self.hline_arms(&mut arms, x, right, y);
+        self.hline_arms(&mut arms, x, right, bottom);
+        self.vline_arms(&mut arms, x, y, bottom);
+        self.vline_arms(&mut arms, right, y, bottom);
+        self.draw_arms(arms, weight);
+    }
+
+    /// Draw a line through each of `points` in turn.
+    ///
+    /// Consecutive points are joined by horizontal or vertical segments, and
+    /// the segments are stacked where they meet, so each bend becomes a
+    /// corner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unicode_line_stacker::{Canvas, Weight};
+    ///
+    /// let mut canvas = Canvas::new(4, 3);
+    /// canvas.polyline(&[(0, 0), (2, 0), (2, 2), (3, 2)], Weight::Heavy);
+    ///
+    /// assert_eq!("╺━┓ \n  ┃ \n  ┗╸", canvas.to_string());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if two consecutive points are in neither the same row nor the
+    /// same column.
+    pub fn polyline(&mut self, points: &[(usize, usize)], weight: Weight) {
+        let mut arms = Arms::new();
+        for segment in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+            if y0 == y1 {
+                self.hline_arms(&mut arms, x0, x1, y0);
+            } else if x0 == x1 {
+                self.vline_arms(&mut arms, x0, y0, y1);
+            } else {
+                panic!("Polyline segments must be horizontal or vertical");
+            }
+        }
+        self.draw_arms(arms, weight);
+    }
+
+    /// Fill the canvas with spaces.
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            *cell = ' ';
+        }
+    }
+
+    /// Change the size of the canvas.
+    ///
+    /// Chars inside both the old and new size are kept, and any new cells
+    /// are filled with spaces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unicode_line_stacker::Canvas;
+    ///
+    /// let mut canvas = Canvas::new(2, 1);
+    /// canvas.set(0, 0, '╶');
+    /// canvas.set(1, 0, '╴');
+    /// canvas.resize(1, 2);
+    ///
+    /// assert_eq!("╶\n ", canvas.to_string());
+    /// ```
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let mut cells = vec![' '; width * height];
+        for (new_row, old_row) in cells.chunks_mut(width.max(1)).zip(self.rows()) {
+            let len = width.min(self.width);
+            new_row[..len].copy_from_slice(&old_row[..len]);
+        }
+
+        self.width = width;
+        self.height = height;
+        self.cells = cells;
+    }
+
+    /// Return the chars in row `y`, or `None` if it is outside the canvas.
+    #[inline]
+    pub fn row(&self, y: usize) -> Option<&[char]> {
+        if y < self.height {
+            Some(&self.cells[y * self.width..(y + 1) * self.width])
+        } else {
+            None
+        }
+    }
+
+    /// Iterate over the rows of the canvas, from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[char]> {
+        (0..self.height).map(move |y| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    /// Return the position of the cell next to `(x, y)` in `direction`, which
+    /// must be a single direction, or `None` if it is outside the canvas.
+    #[inline]
+    pub(crate) fn neighbour(
+        &self,
+        x: usize,
+        y: usize,
+        direction: Directions,
+    ) -> Option<(usize, usize)> {
+        let (x, y) = match direction {
+            Directions::UP => (Some(x), y.checked_sub(1)),
+            Directions::RIGHT => (x.checked_add(1), Some(y)),
+            Directions::DOWN => (Some(x), y.checked_add(1)),
+            Directions::LEFT => (x.checked_sub(1), Some(y)),
+            _ => (None, None),
+        };
+
+        match (x, y) {
+            (Some(x), Some(y)) if x < self.width && y < self.height => Some((x, y)),
+            _ => None,
+        }
+    }
+
+    /// Return the lines of every cell in row order, or `None` for cells that
+    /// are not line-drawing chars without diagonals.
+    pub(crate) fn orthogonal_lines(&self) -> Vec<Option<Lines>> {
+        self.cells.iter().map(|&c| orthogonal_lines(c)).collect()
+    }
+
+    /// Return the arms of the cell at `(x, y)` whose neighbour has an arm
+    /// pointing back, where `cells` holds the lines of every cell in row
+    /// order, as returned by [`orthogonal_lines`](Canvas::orthogonal_lines).
+    pub(crate) fn connected_arms(&self, cells: &[Option<Lines>], x: usize, y: usize) -> Directions {
+        let directions = match cells[y * self.width + x] {
+            Some(lines) => lines.directions(),
+            None => return Directions::NONE,
+        };
+
+        let mut connected = Directions::NONE;
+        for direction in directions {
+            let reaches_back = self
+                .neighbour(x, y, direction)
+                .and_then(|(nx, ny)| cells[ny * self.width + nx])
+                .is_some_and(|n| n.directions().contains(direction.rotate_180()));
+            if reaches_back {
+                connected |= direction;
+            }
+        }
+
+        connected
+    }
+
+    /// Add the arms of a horizontal line to `arms`, clipped to the canvas.
+    fn hline_arms(&self, arms: &mut Arms, x0: usize, x1: usize, y: usize) {
+        let (start, end) = (x0.min(x1), x0.max(x1));
+        if start >= self.width || y >= self.height {
+            return;
+        }
+
+        for x in start..=end.min(self.width - 1) {
+            let directions = arms.entry((x, y)).or_default();
+            if x > start {
+                *directions |= Directions::LEFT;
+            }
+            if x < end {
+                *directions |= Directions::RIGHT;
+            }
+        }
+    }
+
+    /// Add the arms of a vertical line to `arms`, clipped to the canvas.
+    fn vline_arms(&self, arms: &mut Arms, x: usize, y0: usize, y1: usize) {
+        let (start, end) = (y0.min(y1), y0.max(y1));
+        if x >= self.width || start >= self.height {
+            return;
+        }
+
+        for y in start..=end.min(self.height - 1) {
+            let directions = arms.entry((x, y)).or_default();
+            if y > start {
+                *directions |= Directions::UP;
+            }
+            if y < end {
+                *directions |= Directions::DOWN;
+            }
+        }
+    }
+
+    /// Stack every cell of `arms` onto the canvas.
+    ///
+    /// The arms of a whole shape are collected before any of them are
+    /// drawn, since Unicode has no glyph for a lone double arm: drawing a
+    /// double corner one arm at a time would draw it light.
+    fn draw_arms(&mut self, arms: Arms, weight: Weight) {
+        for ((x, y), directions) in arms {
+            self.draw_lines(x, y, Lines::new(directions, weight));
+        }
+    }
+
+    /// Stack `lines` onto the cell at `(x, y)`, keeping every style of both
+    /// until the result is turned into a char.
+    fn draw_lines(&mut self, x: usize, y: usize, lines: Lines) {
+        let below = match self.get(x, y) {
+            Some(c) => c,
+            None => return,
+        };
+
+        match Lines::try_from(below).ok().map(|b| (b, b.stack(lines))) {
+            // Keep the exact glyph, e.g. a rounded corner, if nothing is added.
+            Some((below, Some(stacked))) if stacked == below => {}
+            Some((_, Some(stacked))) => self.set(x, y, char::from(stacked)),
+            _ => self.draw(x, y, char::from(lines)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+}
+
+/// Parse a canvas from lines of text.
+///
+/// The canvas is as wide as the longest line, and shorter lines are padded
+/// with spaces.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::Canvas;
+///
+/// let canvas = Canvas::from("┌─┐\n└");
+///
+/// assert_eq!((3, 2), (canvas.width(), canvas.height()));
+/// assert_eq!("┌─┐\n└  ", canvas.to_string());
+/// ```
+impl From<&str> for Canvas {
+    fn from(s: &str) -> Canvas {
+        let lines: Vec<&str> = s.lines().collect();
+        let width = lines.iter().map(|line| line.chars().count()).max();
+        let mut canvas = Canvas::new(width.unwrap_or(0), lines.len());
+
+        for (y, line) in lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                canvas.set(x, y, c);
+            }
+        }
+
+        canvas
+    }
+}
+
+/// Render the canvas as its rows, separated by newlines.
+impl fmt::Display for Canvas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                f.write_str("\n")?;
+            }
+            for &c in row {
+                write!(f, "{}", c)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn out_of_bounds_writes_are_clipped() {
+        let mut canvas = Canvas::new(2, 2);
+
+        canvas.set(2, 0, 'x');
+        canvas.draw(0, 2, '\u{2500}');
+
+        assert_eq!(Canvas::new(2, 2), canvas);
+        assert_eq!(None, canvas.get(2, 0));
+    }
+
+    #[test]
+    fn draw_text_over_lines() {
+        let mut canvas = Canvas::new(1, 1);
+
+        canvas.draw(0, 0, '\u{2500}');
+        canvas.draw(0, 0, ' ');
+        assert_eq!(Some('\u{2500}'), canvas.get(0, 0));
+
+        canvas.draw(0, 0, 'a');
+        assert_eq!(Some('a'), canvas.get(0, 0));
+    }
+
+    #[test]
+    fn draw_keeps_rounded_corners() {
+        let mut canvas = Canvas::new(2, 1);
+
+        canvas.draw(0, 0, '\u{256d}');
+        canvas.set(1, 0, '\u{256e}');
+        canvas.draw(1, 0, ' ');
+
+        assert_eq!("\u{256d}\u{256e}", canvas.to_string());
+    }
+
+    #[test]
+    fn crossing_lines_join() {
+        let mut canvas = Canvas::new(3, 3);
+
+        canvas.hline(2, 0, 1, Weight::Light);
+        canvas.vline(1, 0, 2, Weight::Double);
+
+        assert_eq!(
+            " \u{2577} \n\u{2576}\u{256b}\u{2574}\n \u{2575} ",
+            canvas.to_string()
+        );
+    }
+
+    #[test]
+    fn single_cell_line_draws_nothing() {
+        let mut canvas = Canvas::new(1, 1);
+
+        canvas.hline(0, 0, 0, Weight::Light);
+        canvas.rect(0, 0, 1, 1, Weight::Light);
+
+        assert_eq!(Canvas::new(1, 1), canvas);
+    }
+
+    #[test]
+    fn lines_overwrite_text_and_clip() {
+        let mut canvas = Canvas::new(3, 1);
+        canvas.set(1, 0, 'a');
+
+        canvas.hline(1, 5, 0, Weight::Light);
+
+        assert_eq!(" \u{2576}\u{2500}", canvas.to_string());
+    }
+
+    #[test]
+    fn huge_lines_and_rects_are_clipped() {
+        let mut canvas = Canvas::new(3, 2);
+
+        canvas.hline(1, usize::MAX, 0, Weight::Light);
+        canvas.vline(2, usize::MAX, 1, Weight::Light);
+        canvas.rect(usize::MAX - 1, 0, 4, 4, Weight::Light);
+        canvas.rect(0, 1, usize::MAX, usize::MAX, Weight::Heavy);
+
+        assert_eq!(
+            " \u{2576}\u{2500}\n\u{250f}\u{2501}\u{252f}",
+            canvas.to_string()
+        );
+    }
+
+    #[test]
+    fn double_rect_has_double_corners() {
+        let mut canvas = Canvas::new(4, 3);
+
+        canvas.rect(0, 0, 4, 3, Weight::Double);
+
+        assert_eq!(
+            "\u{2554}\u{2550}\u{2550}\u{2557}\n\u{2551}  \u{2551}\n\u{255a}\u{2550}\u{2550}\u{255d}",
+            canvas.to_string()
+        );
+    }
+
+    #[test]
+    fn adjoining_double_rects_join() {
+        let mut canvas = Canvas::new(5, 2);
+
+        canvas.rect(0, 0, 3, 2, Weight::Double);
+        canvas.rect(2, 0, 3, 2, Weight::Double);
+
+        assert_eq!(
+            "\u{2554}\u{2550}\u{2566}\u{2550}\u{2557}\n\u{255a}\u{2550}\u{2569}\u{2550}\u{255d}",
+            canvas.to_string()
+        );
+    }
+
+    #[test]
+    fn double_polyline_bends_are_double() {
+        let mut canvas = Canvas::new(3, 3);
+
+        canvas.polyline(&[(0, 0), (2, 0), (2, 2)], Weight::Double);
+
+        assert_eq!(
+            "\u{2576}\u{2550}\u{2557}\n  \u{2551}\n  \u{2575}",
+            canvas.to_string()
+        );
+    }
+
+    #[test]
+    fn lines_keep_rounded_corners_they_do_not_change() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set(0, 0, '\u{256d}');
+
+        canvas.hline(0, 1, 0, Weight::Light);
+
+        assert_eq!("\u{256d}\u{2574}", canvas.to_string());
+    }
+
+    #[test]
+    fn rect_inside_rect() {
+        let mut canvas = Canvas::new(4, 3);
+
+        canvas.rect(0, 0, 4, 3, Weight::Heavy);
+        canvas.rect(0, 0, 2, 3, Weight::Light);
+
+        assert_eq!(
+            "\u{250f}\u{252f}\u{2501}\u{2513}\n\u{2503}\u{2502} \u{2503}\n\u{2517}\u{2537}\u{2501}\u{251b}",
+            canvas.to_string()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "horizontal or vertical")]
+    fn polyline_panics_on_diagonal_segment() {
+        Canvas::new(2, 2).polyline(&[(0, 0), (1, 1)], Weight::Light);
+    }
+
+    #[test]
+    fn resize_grows_with_spaces() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas.set(0, 0, '\u{253c}');
+
+        canvas.resize(2, 2);
+
+        assert_eq!("\u{253c} \n  ", canvas.to_string());
+    }
+
+    #[test]
+    fn resize_to_and_from_empty() {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.set(1, 1, 'x');
+
+        canvas.resize(0, 2);
+        assert_eq!("\n", canvas.to_string());
+
+        canvas.resize(2, 1);
+        assert_eq!("  ", canvas.to_string());
+    }
+}
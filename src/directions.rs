@@ -0,0 +1,322 @@
+//! The [`Directions`] set type.
+
+use std::convert::TryFrom;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub, SubAssign};
+
+use crate::{Lines, TryFromCharError};
+
+/// A set of the four directions in which a line-drawing char can have arms.
+///
+/// The bits of a `Directions` are the lowest four bits of the crate's bit
+/// set format: starting from least significant bit, up, right, down, left.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::Directions;
+///
+/// let corner = Directions::DOWN | Directions::RIGHT;
+/// assert_eq!('┌', char::from(corner));
+/// assert!(corner.contains(Directions::DOWN));
+/// assert_eq!(Directions::UP | Directions::LEFT, !corner);
+///
+/// let arms: Vec<_> = corner.iter().collect();
+/// assert_eq!(vec![Directions::RIGHT, Directions::DOWN], arms);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Directions(u8);
+
+impl Directions {
+    /// No directions.
+    pub const NONE: Directions = Directions(0b0000);
+    /// Up.
+    pub const UP: Directions = Directions(0b0001);
+    /// Right.
+    pub const RIGHT: Directions = Directions(0b0010);
+    /// Down.
+    pub const DOWN: Directions = Directions(0b0100);
+    /// Left.
+    pub const LEFT: Directions = Directions(0b1000);
+    /// Up and down.
+    pub const VERTICAL: Directions = Directions(0b0101);
+    /// Left and right.
+    pub const HORIZONTAL: Directions = Directions(0b1010);
+    /// All four directions.
+    pub const ALL: Directions = Directions(0b1111);
+
+    /// Create a set from its bits, or return `None` if `bits >= 16`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unicode_line_stacker::Directions;
+    ///
+    /// assert_eq!(Some(Directions::VERTICAL), Directions::from_bits(0b0101));
+    /// assert_eq!(None, Directions::from_bits(16));
+    /// ```
+    #[inline]
+    pub const fn from_bits(bits: u8) -> Option<Directions> {
+        if bits & !Directions::ALL.0 == 0 {
+            Some(Directions(bits))
+        } else {
+            None
+        }
+    }
+
+    /// Create a set from the lowest four bits of `bits`, ignoring the rest.
+    #[inline]
+    pub const fn from_bits_truncate(bits: u8) -> Directions {
+        Directions(bits & Directions::ALL.0)
+    }
+
+    /// Return the bits of this set.
+    #[inline]
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Return whether this set has no directions.
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Return the number of directions in this set.
+    #[inline]
+    pub const fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Return whether every direction in `other` is also in this set.
+    #[inline]
+    pub const fn contains(self, other: Directions) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Rotate every direction a quarter turn clockwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unicode_line_stacker::Directions;
+    ///
+    /// let corner = Directions::UP | Directions::RIGHT;
+    /// assert_eq!(Directions::RIGHT | Directions::DOWN, corner.rotate_cw());
+    /// ```
+    #[inline]
+    pub const fn rotate_cw(self) -> Directions {
+        Directions(((self.0 << 1) | (self.0 >> 3)) & Directions::ALL.0)
+    }
+
+    /// Rotate every direction a quarter turn counterclockwise.
+    #[inline]
+    pub const fn rotate_ccw(self) -> Directions {
+        Directions(((self.0 >> 1) | (self.0 << 3)) & Directions::ALL.0)
+    }
+
+    /// Rotate every direction a half turn.
+    #[inline]
+    pub const fn rotate_180(self) -> Directions {
+        Directions(((self.0 >> 2) | (self.0 << 2)) & Directions::ALL.0)
+    }
+
+    /// Mirror left and right.
+    #[inline]
+    pub const fn flip_horizontal(self) -> Directions {
+        let left_right = Directions::HORIZONTAL.0;
+        let swapped = Directions(self.0 & left_right).rotate_180().0;
+        Directions((self.0 & !left_right) | swapped)
+    }
+
+    /// Mirror up and down.
+    #[inline]
+    pub const fn flip_vertical(self) -> Directions {
+        let up_down = Directions::VERTICAL.0;
+        let swapped = Directions(self.0 & up_down).rotate_180().0;
+        Directions((self.0 & !up_down) | swapped)
+    }
+
+    /// Iterate over the directions in this set, one at a time, clockwise
+    /// starting from up.
+    #[inline]
+    pub fn iter(self) -> DirectionsIter {
+        DirectionsIter { remaining: self.0 }
+    }
+}
+
+impl BitOr for Directions {
+    type Output = Directions;
+
+    #[inline]
+    fn bitor(self, rhs: Directions) -> Directions {
+        Directions(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Directions {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Directions) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for Directions {
+    type Output = Directions;
+
+    #[inline]
+    fn bitand(self, rhs: Directions) -> Directions {
+        Directions(self.0 & rhs.0)
+    }
+}
+
+impl BitAndAssign for Directions {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: Directions) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl Sub for Directions {
+    type Output = Directions;
+
+    #[inline]
+    fn sub(self, rhs: Directions) -> Directions {
+        Directions(self.0 & !rhs.0)
+    }
+}
+
+impl SubAssign for Directions {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Directions) {
+        self.0 &= !rhs.0;
+    }
+}
+
+impl Not for Directions {
+    type Output = Directions;
+
+    #[inline]
+    fn not(self) -> Directions {
+        Directions(!self.0 & Directions::ALL.0)
+    }
+}
+
+impl IntoIterator for Directions {
+    type Item = Directions;
+    type IntoIter = DirectionsIter;
+
+    #[inline]
+    fn into_iter(self) -> DirectionsIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the directions in a [`Directions`] set.
+///
+/// Returned by [`Directions::iter`].
+#[derive(Clone, Debug)]
+pub struct DirectionsIter {
+    remaining: u8,
+}
+
+impl Iterator for DirectionsIter {
+    type Item = Directions;
+
+    #[inline]
+    fn next(&mut self) -> Option<Directions> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let lowest = self.remaining & self.remaining.wrapping_neg();
+        self.remaining &= !lowest;
+        Some(Directions(lowest))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for DirectionsIter {}
+
+/// Convert a set of directions to the light line-drawing char with those arms.
+impl From<Directions> for char {
+    #[inline]
+    fn from(directions: Directions) -> char {
+        char::from(Lines::from(directions))
+    }
+}
+
+/// Convert a line-drawing char to the set of directions its arms point in,
+/// regardless of their weight.
+///
+/// Fails if the char is unsupported or is a diagonal.
+impl TryFrom<char> for Directions {
+    type Error = TryFromCharError;
+
+    #[inline]
+    fn try_from(c: char) -> Result<Directions, TryFromCharError> {
+        let lines = Lines::try_from(c)?;
+
+        if lines.has_diagonals() {
+            Err(TryFromCharError(c))
+        } else {
+            Ok(lines.directions())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operators() {
+        let corner = Directions::UP | Directions::RIGHT;
+
+        assert_eq!(Directions::UP, corner & Directions::VERTICAL);
+        assert_eq!(Directions::RIGHT, corner - Directions::UP);
+        assert_eq!(Directions::DOWN | Directions::LEFT, !corner);
+        assert_eq!(Directions::NONE, !Directions::ALL);
+    }
+
+    #[test]
+    fn transforms() {
+        let tee = Directions::UP | Directions::RIGHT | Directions::DOWN;
+
+        assert_eq!(!Directions::UP, tee.rotate_cw());
+        assert_eq!(!Directions::DOWN, tee.rotate_ccw());
+        assert_eq!(!Directions::RIGHT, tee.rotate_180());
+        assert_eq!(!Directions::RIGHT, tee.flip_horizontal());
+        assert_eq!(tee, tee.flip_vertical());
+        assert_eq!(tee, tee.rotate_cw().rotate_ccw());
+    }
+
+    #[test]
+    fn iter_in_clockwise_order() {
+        let all: Vec<_> = Directions::ALL.into_iter().collect();
+
+        assert_eq!(
+            vec![
+                Directions::UP,
+                Directions::RIGHT,
+                Directions::DOWN,
+                Directions::LEFT
+            ],
+            all
+        );
+        assert_eq!(0, Directions::NONE.iter().len());
+    }
+
+    #[test]
+    fn char_conversions() {
+        assert_eq!(Ok(Directions::ALL), Directions::try_from('\u{254b}'));
+        assert_eq!(Ok(Directions::NONE), Directions::try_from(' '));
+        assert!(Directions::try_from('\u{2571}').is_err());
+        assert!(Directions::try_from('x').is_err());
+        assert_eq!('\u{2524}', char::from(!Directions::RIGHT));
+    }
+}
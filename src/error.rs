@@ -0,0 +1,74 @@
+//! The [`StackError`] type returned by the crate's fallible functions.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::TryFromCharError;
+
+/// Which operand of a stack caused an error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operand {
+    /// The first operand, which is drawn underneath.
+    First,
+    /// The second operand, which is drawn on top.
+    Second,
+}
+
+/// The error returned by [`try_stack`](crate::try_stack),
+/// [`try_char_to_bits`](crate::try_char_to_bits) and
+/// [`try_bits_to_char`](crate::try_bits_to_char).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StackError {
+    /// The char is not a supported line-drawing char.
+    UnsupportedChar(char),
+    /// An operand of a stack is not a supported line-drawing char.
+    UnsupportedOperand(char, Operand),
+    /// The two chars are supported, but cannot be drawn together, as with a
+    /// diagonal and a horizontal line, or with arms of different weights
+    /// under [`Precedence::Error`](crate::Precedence::Error).
+    Incompatible(char, char),
+    /// The bit set is not valid.
+    InvalidBits(u32),
+}
+
+impl fmt::Display for StackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            StackError::UnsupportedChar(c) => write!(f, "unsupported line-drawing char {:?}", c),
+            StackError::UnsupportedOperand(c, Operand::First) => {
+                write!(f, "unsupported line-drawing char {:?} as first operand", c)
+            }
+            StackError::UnsupportedOperand(c, Operand::Second) => {
+                write!(f, "unsupported line-drawing char {:?} as second operand", c)
+            }
+            StackError::Incompatible(a, b) => write!(f, "cannot stack {:?} and {:?}", a, b),
+            StackError::InvalidBits(bits) => write!(f, "invalid line-drawing bit set {:#b}", bits),
+        }
+    }
+}
+
+impl Error for StackError {}
+
+impl From<TryFromCharError> for StackError {
+    #[inline]
+    fn from(err: TryFromCharError) -> StackError {
+        StackError::UnsupportedChar(err.unsupported_char())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            "unsupported line-drawing char 'x' as second operand",
+            StackError::UnsupportedOperand('x', Operand::Second).to_string()
+        );
+        assert_eq!(
+            "invalid line-drawing bit set 0b10000",
+            StackError::InvalidBits(16).to_string()
+        );
+    }
+}
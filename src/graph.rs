@@ -0,0 +1,274 @@
+//! Recovering which lines on a canvas connect to which.
+
+use std::collections::HashSet;
+
+use crate::{Canvas, Dash, Directions, Lines, Weight};
+
+/// The connectivity graph of the lines on a canvas, as returned by
+/// [`extract_graph`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Graph {
+    /// The junctions and endpoints.
+    pub nodes: Vec<Node>,
+    /// The lines between them.
+    pub edges: Vec<Edge>,
+}
+
+/// A junction or endpoint in a [`Graph`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Node {
+    /// The column of the cell.
+    pub x: usize,
+    /// The row of the cell.
+    pub y: usize,
+    /// The char in the cell.
+    pub ch: char,
+}
+
+/// A line between two nodes of a [`Graph`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Edge {
+    /// The index of the node the line starts at.
+    pub from: usize,
+    /// The index of the node the line ends at, which is `from` if the line
+    /// is a loop.
+    pub to: usize,
+    /// The `(x, y)` position of every cell along the line, from the start
+    /// node to the end node, inclusive.
+    pub path: Vec<(usize, usize)>,
+    /// The weight of every arm along the line, or `None` if it changes along
+    /// the way.
+    pub weight: Option<Weight>,
+    /// The dash pattern of every cell between the two nodes, or `None` if it
+    /// changes along the way.  Lines with no cells between the nodes are
+    /// solid.
+    pub dash: Option<Dash>,
+}
+
+/// Extract the graph of junctions and the lines between them from the
+/// canvas.
+///
+/// Two neighbouring cells are connected if each has an arm pointing at the
+/// other.  Every line-drawing cell without diagonals that is not connected
+/// to exactly two neighbours is a node: junctions like `┼`, and endpoints
+/// like a `─` that meets text.  Edges follow the cells that are connected to
+/// exactly two neighbours, through straight lines and corners, from one node
+/// to the next.  A closed loop with no junctions gets its first cell, in row
+/// order, as a node, so that it still has an edge.
+///
+/// Nodes are returned row by row.  Edges are returned in the order of their
+/// start nodes, and for each start node clockwise from up.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::{extract_graph, Canvas, Weight};
+///
+/// let canvas = Canvas::from("a━━┓\n   ┣━b\n   ┃\n   c");
+/// let graph = extract_graph(&canvas);
+///
+/// let nodes: Vec<char> = graph.nodes.iter().map(|node| node.ch).collect();
+/// assert_eq!(vec!['━', '┣', '━', '┃'], nodes);
+///
+/// assert_eq!(3, graph.edges.len());
+/// let first = &graph.edges[0];
+/// assert_eq!((0, 1), (first.from, first.to));
+/// assert_eq!(vec![(1, 0), (2, 0), (3, 0), (3, 1)], first.path);
+/// assert_eq!(Some(Weight::Heavy), first.weight);
+/// ```
+pub fn extract_graph(canvas: &Canvas) -> Graph {
+    let cells: Vec<Option<Lines>> = canvas
+        .orthogonal_lines()
+        .into_iter()
+        .map(|lines| lines.filter(|lines| !lines.directions().is_empty()))
+        .collect();
+    let connected = (0..cells.len())
+        .map(|i| canvas.connected_arms(&cells, i % canvas.width(), i / canvas.width()))
+        .collect();
+
+    let mut extractor = Extractor {
+        canvas,
+        node_indices: vec![None; cells.len()],
+        visited: vec![false; cells.len()],
+        cells,
+        connected,
+        walked: HashSet::new(),
+        graph: Graph::default(),
+    };
+
+    let mut starts = Vec::new();
+    for i in 0..extractor.cells.len() {
+        if extractor.cells[i].is_some() && extractor.connected[i].len() != 2 {
+            extractor.add_node(i);
+            starts.push(i);
+        }
+    }
+    for start in starts {
+        extractor.walk_edges(start);
+    }
+
+    // Whatever is left unvisited is made of loops with no nodes.
+    for i in 0..extractor.cells.len() {
+        if extractor.cells[i].is_some() && !extractor.visited[i] {
+            extractor.add_node(i);
+            extractor.walk_edges(i);
+        }
+    }
+
+    extractor.graph
+}
+
+struct Extractor<'a> {
+    canvas: &'a Canvas,
+    cells: Vec<Option<Lines>>,
+    connected: Vec<Directions>,
+    node_indices: Vec<Option<usize>>,
+    /// The arms that have already been followed, as `(cell, direction)`.
+    walked: HashSet<(usize, Directions)>,
+    visited: Vec<bool>,
+    graph: Graph,
+}
+
+impl Extractor<'_> {
+    fn add_node(&mut self, i: usize) {
+        let (x, y) = self.position(i);
+        self.node_indices[i] = Some(self.graph.nodes.len());
+        self.graph.nodes.push(Node {
+            x,
+            y,
+            ch: self.canvas.get(x, y).expect("node is on the canvas"),
+        });
+    }
+
+    /// Follow every connected arm of the node at `start` that has not been
+    /// followed yet, adding an edge for each.
+    fn walk_edges(&mut self, start: usize) {
+        let from = self.node_indices[start].expect("walks start at a node");
+        self.visited[start] = true;
+
+        for first in self.connected[start] {
+            if !self.walked.insert((start, first)) {
+                continue;
+            }
+
+            let mut path = vec![self.position(start)];
+            let mut weights = vec![self.arm_weight(start, first)];
+            let mut dashes = Vec::new();
+            let (mut current, mut direction) = (start, first);
+
+            let to = loop {
+                let (x, y) = self.position(current);
+                let (nx, ny) = self
+                    .canvas
+                    .neighbour(x, y, direction)
+                    .expect("connected arms stay on the canvas");
+                current = ny * self.canvas.width() + nx;
+                let back = direction.rotate_180();
+                self.visited[current] = true;
+                path.push((nx, ny));
+                weights.push(self.arm_weight(current, back));
+
+                if let Some(to) = self.node_indices[current] {
+                    self.walked.insert((current, back));
+                    break to;
+                }
+
+                direction = self.connected[current] - back;
+                weights.push(self.arm_weight(current, direction));
+                dashes.push(self.cells[current].expect("path cells have lines").dash());
+            };
+
+            self.graph.edges.push(Edge {
+                from,
+                to,
+                path,
+                weight: shared(&weights),
+                dash: if dashes.is_empty() {
+                    Some(Dash::Solid)
+                } else {
+                    shared(&dashes)
+                },
+            });
+        }
+    }
+
+    fn arm_weight(&self, i: usize, direction: Directions) -> Weight {
+        self.cells[i]
+            .and_then(|lines| lines.weight(direction))
+            .expect("connected arms are present")
+    }
+
+    fn position(&self, i: usize) -> (usize, usize) {
+        (i % self.canvas.width(), i / self.canvas.width())
+    }
+}
+
+fn shared<T: Copy + PartialEq>(values: &[T]) -> Option<T> {
+    let first = *values.first()?;
+    if values.iter().all(|&value| value == first) {
+        Some(first)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn box_without_junctions_is_a_loop() {
+        let graph = extract_graph(&Canvas::from(
+            "\u{250c}\u{2500}\u{2510}\n\u{2514}\u{2500}\u{2518}",
+        ));
+
+        assert_eq!(1, graph.nodes.len());
+        assert_eq!('\u{250c}', graph.nodes[0].ch);
+        assert_eq!(1, graph.edges.len());
+
+        let edge = &graph.edges[0];
+        assert_eq!((0, 0), (edge.from, edge.to));
+        assert_eq!(
+            vec![(0, 0), (1, 0), (2, 0), (2, 1), (1, 1), (0, 1), (0, 0)],
+            edge.path
+        );
+        assert_eq!(Some(Weight::Light), edge.weight);
+        assert_eq!(Some(Dash::Solid), edge.dash);
+    }
+
+    #[test]
+    fn boxes_joined_by_a_line() {
+        let graph = extract_graph(&Canvas::from(
+            "\u{250c}\u{2510} \u{250c}\u{2510}\n\
+             \u{2502}\u{251c}\u{2550}\u{2524}\u{2502}\n\
+             \u{2514}\u{2518} \u{2514}\u{2518}",
+        ));
+
+        let positions: Vec<(usize, usize)> = graph.nodes.iter().map(|n| (n.x, n.y)).collect();
+        assert_eq!(vec![(1, 1), (3, 1)], positions);
+
+        let ends: Vec<(usize, usize)> = graph.edges.iter().map(|e| (e.from, e.to)).collect();
+        assert_eq!(vec![(0, 0), (0, 1), (1, 1)], ends);
+
+        let line = &graph.edges[1];
+        assert_eq!(vec![(1, 1), (2, 1), (3, 1)], line.path);
+        assert_eq!(None, line.weight);
+    }
+
+    #[test]
+    fn dashed_line_between_stubs() {
+        let graph = extract_graph(&Canvas::from("\u{2576}\u{2504}\u{2504}\u{2574}"));
+
+        assert_eq!(2, graph.nodes.len());
+        assert_eq!(1, graph.edges.len());
+        assert_eq!(Some(Dash::Triple), graph.edges[0].dash);
+    }
+
+    #[test]
+    fn isolated_cells_are_nodes_without_edges() {
+        let graph = extract_graph(&Canvas::from("\u{2500} \u{253c}\n  x"));
+
+        assert_eq!(2, graph.nodes.len());
+        assert!(graph.edges.is_empty());
+    }
+}
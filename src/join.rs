@@ -0,0 +1,179 @@
+//! Repairing the connections between neighbouring cells.
+
+use crate::{is_rounded, round_corner, Canvas, Lines};
+
+/// Options controlling how [`auto_join`] repairs arms that point at a
+/// neighbour that does not reach back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct JoinOptions {
+    /// Add the missing arm to the neighbour, if the neighbour is a
+    /// line-drawing char with arms of its own.
+    pub extend: bool,
+    /// Remove the arm if it still points at a neighbour that does not reach
+    /// back after extending.
+    pub trim: bool,
+}
+
+impl Default for JoinOptions {
+    /// Both extend and trim.
+    fn default() -> JoinOptions {
+        JoinOptions {
+            extend: true,
+            trim: true,
+        }
+    }
+}
+
+/// Add or remove arms on the canvas so that every arm points at a neighbour
+/// with an arm pointing back.
+///
+/// Only cells with horizontal or vertical arms take part.  Spaces, text,
+/// diagonals and the edges of the canvas never reach back, and are never
+/// changed.  An arm added by extending has the weight of the arm pointing at
+/// it and the dash pattern of the cell it is added to, and is stacked as
+/// with [`stack`](crate::stack).  All cells are checked against the canvas
+/// as it was before the pass, so one pass is enough, and running it again
+/// changes nothing.
+///
+/// Cells that need no change keep their exact char, and changed corners that
+/// were rounded stay rounded.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::{auto_join, Canvas, JoinOptions};
+///
+/// let mut canvas = Canvas::from(" │\n─│\n ╵");
+/// auto_join(&mut canvas, JoinOptions::default());
+/// assert_eq!(" ╷\n╶┤\n ╵", canvas.to_string());
+///
+/// let mut canvas = Canvas::from("─│\n x");
+/// let trim = JoinOptions {
+///     extend: false,
+///     trim: true,
+/// };
+/// auto_join(&mut canvas, trim);
+/// assert_eq!("  \n x", canvas.to_string());
+/// ```
+pub fn auto_join(canvas: &mut Canvas, options: JoinOptions) {
+    let width = canvas.width();
+    let original = canvas.orthogonal_lines();
+    let mut joined = original.clone();
+
+    if options.extend {
+        for (i, lines) in original.iter().enumerate() {
+            let lines = match lines {
+                Some(lines) => *lines,
+                None => continue,
+            };
+
+            for direction in lines.directions() {
+                let (x, y) = match canvas.neighbour(i % width, i / width, direction) {
+                    Some(position) => position,
+                    None => continue,
+                };
+                let n = y * width + x;
+                let neighbour = match original[n] {
+                    Some(neighbour) if !neighbour.directions().is_empty() => neighbour,
+                    _ => continue,
+                };
+
+                let back = direction.rotate_180();
+                if !neighbour.directions().contains(back) {
+                    let weight = lines.weight(direction).expect("arm is present");
+                    let arm = Lines::new(back, weight).with_dash(neighbour.dash());
+                    let current = joined[n].expect("neighbour has lines");
+                    joined[n] = current.stack(arm).or(Some(current));
+                }
+            }
+        }
+    }
+
+    if options.trim {
+        let extended = joined.clone();
+        for (i, lines) in extended.iter().enumerate() {
+            let lines = match lines {
+                Some(lines) => *lines,
+                None => continue,
+            };
+
+            let connected = canvas.connected_arms(&extended, i % width, i / width);
+            let dangling = lines.directions() - connected;
+
+            if !dangling.is_empty() {
+                joined[i] = Some(lines.unstack(dangling.into()));
+            }
+        }
+    }
+
+    for (i, (before, after)) in original.iter().zip(&joined).enumerate() {
+        if let (Some(before), Some(after)) = (before, after) {
+            if before != after {
+                let (x, y) = (i % width, i / width);
+                let old = canvas.get(x, y).expect("cell is on the canvas");
+                let new = char::from(*after);
+                canvas.set(
+                    x,
+                    y,
+                    if is_rounded(old) {
+                        round_corner(new)
+                    } else {
+                        new
+                    },
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXTEND: JoinOptions = JoinOptions {
+        extend: true,
+        trim: false,
+    };
+
+    fn joined(s: &str, options: JoinOptions) -> String {
+        let mut canvas = Canvas::from(s);
+        auto_join(&mut canvas, options);
+        canvas.to_string()
+    }
+
+    #[test]
+    fn extend_only_keeps_dangling_arms() {
+        assert_eq!("\u{2500}\u{2524} ", joined("\u{2500}\u{2502} ", EXTEND));
+    }
+
+    #[test]
+    fn extend_uses_weight_of_arm() {
+        assert_eq!("\u{2501}\u{2525}", joined("\u{2501}\u{2502}", EXTEND));
+    }
+
+    #[test]
+    fn text_and_diagonals_are_never_joined() {
+        assert_eq!(
+            "a \u{2571}",
+            joined("a\u{2500}\u{2571}", JoinOptions::default())
+        );
+    }
+
+    #[test]
+    fn rounded_corners_stay_rounded() {
+        assert_eq!(
+            "\u{2576}\u{256e}\n \u{2575}",
+            joined("\u{2500}\u{256d}\n \u{2575}", JoinOptions::default())
+        );
+    }
+
+    #[test]
+    fn second_pass_changes_nothing() {
+        let once = joined(
+            "\u{250c}\u{2500}\u{2534}\n\u{2502}x\u{253c}",
+            JoinOptions::default(),
+        );
+
+        assert_eq!(once, joined(&once, JoinOptions::default()));
+    }
+}
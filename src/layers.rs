@@ -0,0 +1,274 @@
+//! Building a canvas from independent layers.
+
+use std::collections::HashMap;
+
+use crate::Canvas;
+
+/// A sparse grid of chars that makes up one layer of a [`LayeredCanvas`].
+///
+/// Only the cells that have been set are stored.  Layers with a higher z
+/// order are drawn on top, and hidden layers are not drawn at all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layer {
+    cells: HashMap<(usize, usize), char>,
+    z: i32,
+    visible: bool,
+}
+
+impl Layer {
+    /// Create an empty, visible layer with z order `z`.
+    pub fn new(z: i32) -> Layer {
+        Layer {
+            cells: HashMap::new(),
+            z,
+            visible: true,
+        }
+    }
+
+    /// Return the char at `(x, y)`, or `None` if it has not been set.
+    #[inline]
+    pub fn get(&self, x: usize, y: usize) -> Option<char> {
+        self.cells.get(&(x, y)).copied()
+    }
+
+    /// Set the char at `(x, y)`.
+    ///
+    /// A space is transparent when the layer is flattened, but is still
+    /// stored.
+    #[inline]
+    pub fn set(&mut self, x: usize, y: usize, ch: char) {
+        self.cells.insert((x, y), ch);
+    }
+
+    /// Unset the char at `(x, y)` and return it, if there was one.
+    #[inline]
+    pub fn remove(&mut self, x: usize, y: usize) -> Option<char> {
+        self.cells.remove(&(x, y))
+    }
+
+    /// Unset every char.
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// Iterate over the cells that have been set, as `((x, y), ch)`, in no
+    /// particular order.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), char)> + '_ {
+        self.cells.iter().map(|(&position, &ch)| (position, ch))
+    }
+
+    /// Return the z order of the layer.
+    #[inline]
+    pub fn z(&self) -> i32 {
+        self.z
+    }
+
+    /// Change the z order of the layer.
+    #[inline]
+    pub fn set_z(&mut self, z: i32) {
+        self.z = z;
+    }
+
+    /// Return whether the layer is drawn when flattened.
+    #[inline]
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Show or hide the layer.
+    #[inline]
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+}
+
+/// Create a visible layer with z order 0 holding every char of `canvas`
+/// other than spaces.
+impl From<&Canvas> for Layer {
+    fn from(canvas: &Canvas) -> Layer {
+        let mut layer = Layer::new(0);
+        for (y, row) in canvas.rows().enumerate() {
+            for (x, &ch) in row.iter().enumerate() {
+                if ch != ' ' {
+                    layer.set(x, y, ch);
+                }
+            }
+        }
+
+        layer
+    }
+}
+
+/// A stack of [`Layer`]s that are flattened into a single [`Canvas`].
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::{Canvas, Layer, LayeredCanvas, Weight};
+///
+/// let mut border = Canvas::new(5, 3);
+/// border.rect(0, 0, 5, 3, Weight::Light);
+///
+/// let mut highlight = Layer::new(1);
+/// highlight.set(2, 0, '┃');
+/// highlight.set(2, 1, '┃');
+/// highlight.set(2, 2, '┃');
+///
+/// let mut label = Layer::new(2);
+/// label.set(1, 1, 'a');
+/// label.set(2, 1, 'b');
+///
+/// let mut canvas = LayeredCanvas::new(5, 3);
+/// canvas.push(Layer::from(&border));
+/// let highlight = canvas.push(highlight);
+/// canvas.push(label);
+/// assert_eq!("┌─╂─┐\n│ab │\n└─╂─┘", canvas.flatten().to_string());
+///
+/// canvas.layer_mut(highlight).unwrap().set_visible(false);
+/// assert_eq!("┌───┐\n│ab │\n└───┘", canvas.flatten().to_string());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayeredCanvas {
+    width: usize,
+    height: usize,
+    layers: Vec<Layer>,
+}
+
+impl LayeredCanvas {
+    /// Create a layered canvas with no layers.
+    pub fn new(width: usize, height: usize) -> LayeredCanvas {
+        LayeredCanvas {
+            width,
+            height,
+            layers: Vec::new(),
+        }
+    }
+
+    /// Return the width of the canvas.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Return the height of the canvas.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Add a layer and return its index.
+    pub fn push(&mut self, layer: Layer) -> usize {
+        self.layers.push(layer);
+        self.layers.len() - 1
+    }
+
+    /// Return the layer at `index`, or `None` if there is no such layer.
+    #[inline]
+    pub fn layer(&self, index: usize) -> Option<&Layer> {
+        self.layers.get(index)
+    }
+
+    /// Return the layer at `index` mutably, or `None` if there is no such
+    /// layer.
+    #[inline]
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut Layer> {
+        self.layers.get_mut(index)
+    }
+
+    /// Iterate over the layers in the order they were added.
+    pub fn layers(&self) -> impl Iterator<Item = &Layer> {
+        self.layers.iter()
+    }
+
+    /// Draw every visible layer onto a new canvas, from the lowest z order to
+    /// the highest, and return it.
+    ///
+    /// Each char is drawn with [`Canvas::draw`], so line-drawing chars stack,
+    /// other chars replace whatever is underneath, and spaces are
+    /// transparent.  Layers with the same z order are drawn in the order they
+    /// were added, and cells outside the canvas are clipped.
+    pub fn flatten(&self) -> Canvas {
+        let mut visible: Vec<&Layer> = self.layers.iter().filter(|l| l.visible).collect();
+        visible.sort_by_key(|layer| layer.z);
+
+        let mut canvas = Canvas::new(self.width, self.height);
+        for layer in visible {
+            for ((x, y), ch) in layer.iter() {
+                canvas.draw(x, y, ch);
+            }
+        }
+
+        canvas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn higher_z_is_drawn_on_top_regardless_of_order() {
+        let mut top = Layer::new(5);
+        top.set(0, 0, 'b');
+        let mut bottom = Layer::new(-1);
+        bottom.set(0, 0, 'a');
+        bottom.set(1, 0, '\u{2500}');
+
+        let mut canvas = LayeredCanvas::new(2, 1);
+        canvas.push(top);
+        let bottom = canvas.push(bottom);
+        assert_eq!("b\u{2500}", canvas.flatten().to_string());
+
+        canvas.layer_mut(bottom).unwrap().set_z(10);
+        assert_eq!("a\u{2500}", canvas.flatten().to_string());
+    }
+
+    #[test]
+    fn equal_z_keeps_insertion_order() {
+        let mut first = Layer::new(0);
+        first.set(0, 0, 'a');
+        let mut second = Layer::new(0);
+        second.set(0, 0, 'b');
+
+        let mut canvas = LayeredCanvas::new(1, 1);
+        canvas.push(first);
+        canvas.push(second);
+
+        assert_eq!("b", canvas.flatten().to_string());
+    }
+
+    #[test]
+    fn spaces_are_transparent_and_cells_are_clipped() {
+        let mut bottom = Layer::new(0);
+        bottom.set(0, 0, '\u{2502}');
+        let mut top = Layer::new(1);
+        top.set(0, 0, ' ');
+        top.set(3, 3, 'x');
+
+        let mut canvas = LayeredCanvas::new(1, 1);
+        canvas.push(bottom);
+        canvas.push(top);
+
+        assert_eq!("\u{2502}", canvas.flatten().to_string());
+    }
+
+    #[test]
+    fn rounded_corners_survive_flattening() {
+        let mut layer = Layer::new(0);
+        layer.set(0, 0, '\u{256d}');
+
+        let mut canvas = LayeredCanvas::new(1, 1);
+        canvas.push(layer);
+
+        assert_eq!("\u{256d}", canvas.flatten().to_string());
+    }
+
+    #[test]
+    fn layer_from_canvas_skips_spaces() {
+        let layer = Layer::from(&Canvas::from("a \n b"));
+
+        assert_eq!(Some('a'), layer.get(0, 0));
+        assert_eq!(None, layer.get(1, 0));
+        assert_eq!(2, layer.iter().count());
+    }
+}
@@ -10,11 +10,70 @@
 //! // top (least significant to most significant), 1 means "on" and 0 means "off."
 //! let c = unicode_line_stacker::bits_to_char(0b1011);
 //! assert_eq!('┴', c);
+//!
+//! // The next four bits mark the corresponding arms as heavy.
+//! let c = unicode_line_stacker::bits_to_char(0b1011_1011);
+//! assert_eq!('┻', c);
+//!
+//! // The four bits after that mark arms as double.
+//! let c = unicode_line_stacker::bits_to_char(0b1011_0000_1011);
+//! assert_eq!('╩', c);
+//! ```
+//!
+//! The same bit sets are available as the typed [`Lines`] and [`Directions`].
+//!
 //! ```
+//! use std::convert::TryFrom;
+//! use unicode_line_stacker::{Directions, Lines, Weight};
+//!
+//! let lines = Lines::try_from('┴').unwrap();
+//! assert_eq!(Directions::UP | Directions::HORIZONTAL, lines.directions());
+//!
+//! let heavy = Lines::new(lines.directions(), Weight::Heavy);
+//! assert_eq!('┻', char::from(heavy));
+//! ```
+
+use std::convert::TryFrom;
+
+mod batch;
+mod canvas;
+mod directions;
+mod error;
+mod graph;
+mod join;
+mod layers;
+mod lines;
+mod nearest;
+mod transform;
+mod validate;
+
+pub use batch::{overlay, overlay_with, stack_slices, stack_str};
+pub use canvas::Canvas;
+pub use directions::{Directions, DirectionsIter};
+pub use error::{Operand, StackError};
+pub use graph::{extract_graph, Edge, Graph, Node};
+pub use join::{auto_join, JoinOptions};
+pub use layers::{Layer, LayeredCanvas};
+pub use lines::{Dash, Lines, TryFromCharError, Weight};
+pub use nearest::{nearest_char, NearestChar};
+pub use transform::{flip_horizontal, flip_vertical, rotate_180, rotate_ccw, rotate_cw};
+pub use validate::{validate, Diagnostic, DiagnosticKind};
 
 /// Stack two line-drawing characters on top of each other and return the result.
 ///
-/// Returns `None` if one or both of the input characters are unsupported.
+/// Where the same arm is present in both characters, the heavier weight wins:
+/// heavy over double over light (see [`Precedence`] for other choices).  Arms
+/// of different weights are combined into the matching mixed-weight glyph;
+/// see [`bits_to_char`] for what happens when Unicode has no such glyph.
+///
+/// A dashed line stays dashed when stacked with the same dash pattern or
+/// with an empty cell.  Stacking it with anything else draws it solid.
+///
+/// Diagonals stack with each other, but Unicode has no glyphs combining them
+/// with horizontal or vertical lines.
+///
+/// Returns `None` if one or both of the input characters are unsupported, or
+/// if the result cannot be drawn at all.
 ///
 /// # Examples
 ///
@@ -23,82 +82,846 @@
 /// let b = '│';
 /// let result = unicode_line_stacker::stack(a, b);
 /// assert_eq!(Some('┼'), result);
+///
+/// assert_eq!(Some('┣'), unicode_line_stacker::stack('┏', '┗'));
+/// assert_eq!(Some('┿'), unicode_line_stacker::stack('━', '│'));
+/// assert_eq!(Some('╬'), unicode_line_stacker::stack('╔', '╝'));
+/// assert_eq!(Some('╪'), unicode_line_stacker::stack('═', '│'));
+/// assert_eq!(Some('━'), unicode_line_stacker::stack('━', '═'));
+/// assert_eq!(Some('┅'), unicode_line_stacker::stack('┄', '┅'));
+/// assert_eq!(Some('┼'), unicode_line_stacker::stack('┄', '┆'));
+/// assert_eq!(Some('╳'), unicode_line_stacker::stack('╱', '╲'));
+/// assert_eq!(None, unicode_line_stacker::stack('╱', '─'));
+///
+/// // Stacking can be done at compile time.
+/// const CROSS: Option<char> = unicode_line_stacker::stack('─', '│');
+/// assert_eq!(Some('┼'), CROSS);
 /// ```
-pub fn stack(a: char, b: char) -> Option<char> {
-    let look_for_a = char_to_bits(a);
-    let look_for_b = char_to_bits(b);
+pub const fn stack(a: char, b: char) -> Option<char> {
+    stack_chars(a, b, Precedence::Heaviest)
+}
 
-    if look_for_b.is_none() || look_for_b.is_none() {
-        None
+const fn stack_chars(a: char, b: char, precedence: Precedence) -> Option<char> {
+    let bits_a = match lookup_bits(a) {
+        Some(bits) => bits,
+        None => return None,
+    };
+    let bits_b = match lookup_bits(b) {
+        Some(bits) => bits,
+        None => return None,
+    };
+
+    match stack_bits(bits_a, bits_b, precedence) {
+        // Keep the exact glyph when one side adds nothing, e.g. a rounded
+        // corner stacked with a space.
+        Some(bits) if bits == bits_b => Some(b),
+        Some(bits) if bits == bits_a => Some(a),
+        Some(bits) => checked_bits_to_char(bits),
+        None => None,
+    }
+}
+
+/// Like [`stack`], but returns an error describing why the chars could not be
+/// stacked.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::{Operand, StackError};
+///
+/// assert_eq!(Ok('┼'), unicode_line_stacker::try_stack('─', '│'));
+/// assert_eq!(
+///     Err(StackError::UnsupportedOperand('x', Operand::Second)),
+///     unicode_line_stacker::try_stack('─', 'x')
+/// );
+/// assert_eq!(
+///     Err(StackError::Incompatible('╱', '─')),
+///     unicode_line_stacker::try_stack('╱', '─')
+/// );
+/// ```
+pub fn try_stack(a: char, b: char) -> Result<char, StackError> {
+    stack(a, b).ok_or_else(|| stack_error(a, b))
+}
+
+/// Describe why stacking `a` and `b` failed.
+fn stack_error(a: char, b: char) -> StackError {
+    if lookup_bits(a).is_none() {
+        StackError::UnsupportedOperand(a, Operand::First)
+    } else if lookup_bits(b).is_none() {
+        StackError::UnsupportedOperand(b, Operand::Second)
     } else {
-        Some(LINE_DRAWING_CHARS[look_for_a.unwrap() | look_for_b.unwrap()])
+        StackError::Incompatible(a, b)
+    }
+}
+
+/// Remove the lines of `b` from `a` and return the result.
+///
+/// Every arm of `a` that `b` also has is removed, whatever its weight in `b`.
+/// The remaining arms keep their weight.  Diagonals are removed the same way.
+///
+/// Returns `None` if one or both of the input characters are unsupported.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(Some('─'), unicode_line_stacker::unstack('┼', '│'));
+/// assert_eq!(Some('┝'), unicode_line_stacker::unstack('┿', '╸'));
+/// assert_eq!(Some('╲'), unicode_line_stacker::unstack('╳', '╱'));
+/// assert_eq!(Some(' '), unicode_line_stacker::unstack('║', '┃'));
+/// assert_eq!(None, unicode_line_stacker::unstack('┼', 'x'));
+/// ```
+pub const fn unstack(a: char, b: char) -> Option<char> {
+    let bits_a = match lookup_bits(a) {
+        Some(bits) => bits,
+        None => return None,
+    };
+    let bits_b = match lookup_bits(b) {
+        Some(bits) => bits,
+        None => return None,
+    };
+
+    let bits = unstack_bits(bits_a, bits_b);
+    if bits == bits_a {
+        // Nothing was removed, so keep the exact glyph, e.g. a rounded corner.
+        return Some(a);
     }
+
+    resolve_char(bits)
+}
+
+/// Like [`unstack`], but returns an error naming the unsupported char.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::{Operand, StackError};
+///
+/// assert_eq!(Ok('┘'), unicode_line_stacker::try_unstack('┤', '╷'));
+/// assert_eq!(
+///     Err(StackError::UnsupportedOperand('x', Operand::First)),
+///     unicode_line_stacker::try_unstack('x', '╷')
+/// );
+/// ```
+#[inline]
+pub fn try_unstack(a: char, b: char) -> Result<char, StackError> {
+    unstack(a, b).ok_or_else(|| stack_error(a, b))
+}
+
+/// Like [`stack`], but with the output controlled by `options`.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::{CornerStyle, StackOptions};
+///
+/// let options = StackOptions {
+///     corner_style: CornerStyle::Rounded,
+///     ..StackOptions::default()
+/// };
+/// let result = unicode_line_stacker::stack_with_options('╶', '╷', &options);
+/// assert_eq!(Some('╭'), result);
+///
+/// // Only pure corners are rounded.
+/// let result = unicode_line_stacker::stack_with_options('╭', '│', &options);
+/// assert_eq!(Some('├'), result);
+/// ```
+pub const fn stack_with_options(a: char, b: char, options: &StackOptions) -> Option<char> {
+    let c = match stack_chars(a, b, options.precedence) {
+        Some(c) => c,
+        None => match resolve_conflict(a, b, options.policy) {
+            Some(c) => c,
+            None => return None,
+        },
+    };
+
+    Some(match options.corner_style {
+        CornerStyle::Square => c,
+        CornerStyle::Rounded => round_corner(c),
+    })
+}
+
+/// Like [`stack_with_options`], but returns an error describing why the chars
+/// could not be stacked.
+#[inline]
+pub fn try_stack_with_options(
+    a: char,
+    b: char,
+    options: &StackOptions,
+) -> Result<char, StackError> {
+    stack_with_options(a, b, options).ok_or_else(|| stack_error(a, b))
+}
+
+/// Stack two chars on top of each other, using `policy` to decide the result
+/// when they are not both line-drawing chars that can be drawn together.
+///
+/// A space is treated as an empty cell, so stacking any char with a space
+/// gives that char, whatever the policy.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::{stack_with, StackPolicy};
+///
+/// assert_eq!(Ok('┼'), stack_with('─', '│', StackPolicy::TextWins));
+/// assert_eq!(Ok('a'), stack_with('─', 'a', StackPolicy::TextWins));
+/// assert_eq!(Ok('─'), stack_with('─', 'a', StackPolicy::LineWins));
+/// assert_eq!(Ok('a'), stack_with('a', ' ', StackPolicy::BottomWins));
+/// assert!(stack_with('─', 'a', StackPolicy::Error).is_err());
+/// ```
+#[inline]
+pub fn stack_with(a: char, b: char, policy: StackPolicy) -> Result<char, StackError> {
+    let options = StackOptions {
+        policy,
+        ..StackOptions::default()
+    };
+
+    try_stack_with_options(a, b, &options)
+}
+
+/// Options controlling how [`stack_with_options`] draws its result.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct StackOptions {
+    /// How to draw results that are light corners.
+    pub corner_style: CornerStyle,
+    /// What to do when the chars cannot be stacked.
+    pub policy: StackPolicy,
+    /// Which weight wins when the same arm is present in both chars.
+    pub precedence: Precedence,
+}
+
+/// What to do when stacking two chars that are not both line-drawing chars,
+/// or that cannot be drawn together, like `╱` and `─`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StackPolicy {
+    /// Use the char on top.
+    TopWins,
+    /// Use the char underneath.
+    BottomWins,
+    /// Use whichever char is a line-drawing char, or the char on top if both
+    /// or neither are.
+    LineWins,
+    /// Use whichever char is not a line-drawing char, or the char on top if
+    /// both or neither are.
+    TextWins,
+    /// Fail.
+    #[default]
+    Error,
+}
+
+/// Which weight wins when the same arm is present, with different weights, in
+/// both of the chars being stacked.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::{stack_with_options, Precedence, StackOptions};
+///
+/// let options = |precedence| StackOptions {
+///     precedence,
+///     ..StackOptions::default()
+/// };
+///
+/// assert_eq!(Some('━'), stack_with_options('━', '═', &options(Precedence::Heaviest)));
+/// assert_eq!(Some('═'), stack_with_options('━', '═', &options(Precedence::TopWins)));
+/// assert_eq!(Some('┷'), stack_with_options('━', '┴', &options(Precedence::BottomWins)));
+/// assert_eq!(None, stack_with_options('━', '─', &options(Precedence::Error)));
+///
+/// // Arms that are only in one char keep their weight, and the result is
+/// // the closest glyph: there are no glyphs mixing heavy and double.
+/// assert_eq!(Some('┿'), stack_with_options('━', '║', &options(Precedence::TopWins)));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Precedence {
+    /// Heavy wins over double, which wins over light.
+    #[default]
+    Heaviest,
+    /// The weight from the char on top wins.
+    TopWins,
+    /// The weight from the char underneath wins.
+    BottomWins,
+    /// Fail.
+    Error,
+}
+
+const fn resolve_conflict(a: char, b: char, policy: StackPolicy) -> Option<char> {
+    if a == ' ' {
+        return Some(b);
+    }
+    if b == ' ' {
+        return Some(a);
+    }
+
+    let a_is_line = lookup_bits(a).is_some();
+    let b_is_line = lookup_bits(b).is_some();
+
+    match policy {
+        StackPolicy::TopWins => Some(b),
+        StackPolicy::BottomWins => Some(a),
+        StackPolicy::LineWins if a_is_line && !b_is_line => Some(a),
+        StackPolicy::LineWins => Some(b),
+        StackPolicy::TextWins if !a_is_line && b_is_line => Some(a),
+        StackPolicy::TextWins => Some(b),
+        StackPolicy::Error => None,
+    }
+}
+
+/// How to draw light corners.
+///
+/// Rounded corners are always accepted as input and treated as the
+/// equivalent square corner, so `╭` stacks the same way as `┌`.  Unicode has
+/// no rounded heavy or double corners, nor rounded T-junctions or crosses, so
+/// those are always drawn square.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CornerStyle {
+    /// Draw corners as `┌┐└┘`, except that a rounded corner stacked with
+    /// nothing new is returned unchanged.
+    #[default]
+    Square,
+    /// Draw corners as `╭╮╰╯`.
+    Rounded,
+}
+
+const fn round_corner(c: char) -> char {
+    match c {
+        '\u{250c}' => '\u{256d}',
+        '\u{2510}' => '\u{256e}',
+        '\u{2518}' => '\u{256f}',
+        '\u{2514}' => '\u{2570}',
+        _ => c,
+    }
+}
+
+#[inline]
+const fn is_rounded(c: char) -> bool {
+    matches!(c, '\u{256d}'..='\u{2570}')
+}
+
+/// Return the lines of `c` if it is a line-drawing char without diagonals.
+fn orthogonal_lines(c: char) -> Option<Lines> {
+    Lines::try_from(c)
+        .ok()
+        .filter(|lines| !lines.has_diagonals())
 }
 
 /// Convert a line-drawing char to a bitset (or None if the char is unsupported).
 ///
+/// See [`bits_to_char`] for a description of the bitset format.
+///
 /// # Examples
 ///
 /// ```
 /// let c = '┬';
 /// let result = unicode_line_stacker::char_to_bits(c);
 /// assert_eq!(Some(0b1110), result);
+///
+/// assert_eq!(Some(0b1110_1110), unicode_line_stacker::char_to_bits('┳'));
 /// ```
 #[inline]
-pub fn char_to_bits(c: char) -> Option<usize> {
-    // It's likely faster to iterate through 16 chars than it is to try some
-    // HashMap trickery.
-    LINE_DRAWING_CHARS
-        .iter()
-        .enumerate()
-        .find(|&(_, &c2)| c == c2)
-        .map(|tup| tup.0)
+pub const fn char_to_bits(c: char) -> Option<usize> {
+    match lookup_bits(c) {
+        Some(bits) => Some(bits as usize),
+        None => None,
+    }
+}
+
+/// Like [`char_to_bits`], but returns an error naming the unsupported char.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::StackError;
+///
+/// assert_eq!(Ok(0b1110), unicode_line_stacker::try_char_to_bits('┬'));
+/// assert_eq!(
+///     Err(StackError::UnsupportedChar('x')),
+///     unicode_line_stacker::try_char_to_bits('x')
+/// );
+/// ```
+#[inline]
+pub fn try_char_to_bits(c: char) -> Result<usize, StackError> {
+    Ok(Lines::try_from(c)?.bits() as usize)
 }
 
 /// Convert a bitset to a line-drawing char.
 ///
 /// This crate's representation of each line-drawing char is a `u32`
-/// representing a bitset: starting from least significant bit, the bits
-/// represent up, right, down, left, in that order.
+/// representing a bitset: starting from least significant bit, the first
+/// four bits represent up, right, down, left, in that order.  The next four
+/// bits mark the corresponding arm as heavy, and the four after that mark it
+/// as double.  An arm's heavy or double bit may only be set if the arm itself
+/// is present, and an arm cannot be both heavy and double.  The two bits
+/// after that give the dash pattern: 0 for a solid line, or 1, 2 or 3 for a
+/// double, triple or quadruple dash.  The last two bits are the diagonals:
+/// first `╱`, then `╲`.  Diagonals cannot be combined with any other bits.
+///
+/// Unicode only has glyphs mixing single and double lines where the vertical
+/// arms share one style and the horizontal arms share another, and it has no
+/// glyphs mixing double and heavy lines.  Dashed glyphs only exist for
+/// straight light or heavy lines.  If there is no glyph for `bits`, it is
+/// drawn as close as possible instead:
+///
+/// 1. First, on each axis (vertical and horizontal) whose arms are not all
+///    double, any double arm is drawn light.
+/// 2. If there is still no glyph, every double arm is drawn light.
+/// 3. If a dashed line still has no glyph, its heavy arms are drawn light.
+/// 4. If there is still no glyph, the line is drawn solid instead, keeping
+///    its heavy arms, and double arms are drawn light as in steps 1 and 2.
+///
+/// Use [`nearest_char`] to find out which arms were drawn differently.
 ///
 /// # Examples
 ///
 /// ```
 /// assert_eq!('┤', unicode_line_stacker::bits_to_char(0b1101));
+/// assert_eq!('┫', unicode_line_stacker::bits_to_char(0b1101_1101));
+/// assert_eq!('┪', unicode_line_stacker::bits_to_char(0b1100_1101));
+/// assert_eq!('╼', unicode_line_stacker::bits_to_char(0b0010_1010));
+/// assert_eq!('╣', unicode_line_stacker::bits_to_char(0b1101_0000_1101));
+///
+/// // Double up with single down has no glyph, so the vertical axis falls back
+/// // to light while the double horizontal arms are kept.
+/// assert_eq!('╪', unicode_line_stacker::bits_to_char(0b1011_0000_1111));
+///
+/// assert_eq!('┇', unicode_line_stacker::bits_to_char(0b10_0000_0101_0101));
+/// // There are no dashed double lines, but there are dashed light ones.
+/// assert_eq!('┄', unicode_line_stacker::bits_to_char(0b10_1010_0000_1010));
+/// // There are no dashed junctions.
+/// assert_eq!('┬', unicode_line_stacker::bits_to_char(0b10_0000_0000_1110));
+///
+/// assert_eq!('╳', unicode_line_stacker::bits_to_char(0b1100_0000_0000_0000));
 /// ```
 ///
 /// # Panics
 ///
-/// Panics if `bits >= 16`.
+/// Panics if `bits` is not a valid bit set.
 #[inline]
 pub fn bits_to_char(bits: u32) -> char {
-    if bits >= 16 {
-        panic!(
-            "Bit set must be between 0 and 15 inclusive but got {}",
+    match try_bits_to_char(bits) {
+        Ok(c) => c,
+        Err(_) => panic!(
+            "Bit set must describe a supported line-drawing char but got {}",
             bits
-        );
+        ),
+    }
+}
+
+/// Like [`bits_to_char`], but returns an error instead of panicking if `bits`
+/// is not a valid bit set.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::StackError;
+///
+/// assert_eq!(Ok('┤'), unicode_line_stacker::try_bits_to_char(0b1101));
+/// assert_eq!(
+///     Err(StackError::InvalidBits(16)),
+///     unicode_line_stacker::try_bits_to_char(16)
+/// );
+/// ```
+#[inline]
+pub fn try_bits_to_char(bits: u32) -> Result<char, StackError> {
+    checked_bits_to_char(bits).ok_or(StackError::InvalidBits(bits))
+}
+
+/// Like [`bits_to_char`], but returns `None` instead of panicking if `bits` is
+/// not a valid bit set.
+///
+/// Like [`stack`] and [`char_to_bits`], this is a `const fn`, so it can be
+/// used to build tables of glyphs at compile time.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::checked_bits_to_char;
+///
+/// const TEE: Option<char> = checked_bits_to_char(0b1110);
+/// assert_eq!(Some('┬'), TEE);
+/// assert_eq!(None, checked_bits_to_char(16));
+/// ```
+#[inline]
+pub const fn checked_bits_to_char(bits: u32) -> Option<char> {
+    if is_valid(bits) {
+        resolve_char(bits)
+    } else {
+        None
+    }
+}
+
+const ARMS: u32 = 0b1111;
+const VERTICAL: u32 = 0b0101;
+const HORIZONTAL: u32 = 0b1010;
+const HEAVY_SHIFT: u32 = 4;
+const DOUBLE_SHIFT: u32 = 8;
+const DASH_SHIFT: u32 = 12;
+const DASH_MASK: u32 = 0b11 << DASH_SHIFT;
+const RISING_DIAGONAL: u32 = 1 << 14;
+const FALLING_DIAGONAL: u32 = 1 << 15;
+const DIAGONALS: u32 = RISING_DIAGONAL | FALLING_DIAGONAL;
+const VALID_BITS: u32 = (1 << 16) - 1;
+
+/// Look up the glyph for a valid bit set, falling back as described in
+/// [`bits_to_char`] if there is no exact match.
+const fn resolve_char(bits: u32) -> Option<char> {
+    if let Some(c) = lookup_char(bits) {
+        return Some(c);
+    }
+
+    let light = degrade_double(bits);
+    if let Some(c) = lookup_char(light) {
+        return Some(c);
+    }
+
+    // A dash is kept in preference to heavy arms, if that gives a glyph.
+    if let Some(c) = lookup_char(light & !(ARMS << HEAVY_SHIFT)) {
+        return Some(c);
+    }
+
+    let solid = bits & !DASH_MASK;
+    if let Some(c) = lookup_char(solid) {
+        return Some(c);
+    }
+
+    lookup_char(degrade_double(solid))
+}
+
+/// Remove the arms and diagonals of `b` from `a`.
+const fn unstack_bits(a: u32, b: u32) -> u32 {
+    let removed = b & ARMS;
+    let arms = a & ARMS & !removed;
+    let styles = (arms << HEAVY_SHIFT) | (arms << DOUBLE_SHIFT);
+    let dash = if arms == 0 { 0 } else { a & DASH_MASK };
+
+    arms | (a & styles) | dash | (a & DIAGONALS & !b)
+}
+
+/// Combine the bits of two stacked chars, before any fallback is applied.
+///
+/// Returns `None` if the weights of a shared arm conflict and `precedence` is
+/// [`Precedence::Error`].
+const fn stack_bits(a: u32, b: u32, precedence: Precedence) -> Option<u32> {
+    let arms_a = a & ARMS;
+    let arms_b = b & ARMS;
+    let heavy_a = (a >> HEAVY_SHIFT) & ARMS;
+    let heavy_b = (b >> HEAVY_SHIFT) & ARMS;
+    let double_a = (a >> DOUBLE_SHIFT) & ARMS;
+    let double_b = (b >> DOUBLE_SHIFT) & ARMS;
+
+    let (heavy, double) = match precedence {
+        Precedence::Heaviest => {
+            let heavy = heavy_a | heavy_b;
+            (heavy, (double_a | double_b) & !heavy)
+        }
+        Precedence::TopWins => (
+            (heavy_a & !arms_b) | heavy_b,
+            (double_a & !arms_b) | double_b,
+        ),
+        Precedence::BottomWins => (
+            heavy_a | (heavy_b & !arms_a),
+            double_a | (double_b & !arms_a),
+        ),
+        Precedence::Error => {
+            let shared = arms_a & arms_b;
+            if (heavy_a ^ heavy_b) & shared != 0 || (double_a ^ double_b) & shared != 0 {
+                return None;
+            }
+            (heavy_a | heavy_b, double_a | double_b)
+        }
+    };
+
+    let dash_a = a & DASH_MASK;
+    let dash_b = b & DASH_MASK;
+    let dash = if dash_a == dash_b || b == 0 {
+        dash_a
+    } else if a == 0 {
+        dash_b
+    } else {
+        0
+    };
+
+    Some(((a | b) & (ARMS | DIAGONALS)) | (heavy << HEAVY_SHIFT) | (double << DOUBLE_SHIFT) | dash)
+}
+
+const fn is_valid(bits: u32) -> bool {
+    let arms = bits & ARMS;
+    let heavy = (bits >> HEAVY_SHIFT) & ARMS;
+    let double = (bits >> DOUBLE_SHIFT) & ARMS;
+
+    let diagonal_only = bits & DIAGONALS == 0 || bits & !DIAGONALS == 0;
+
+    bits & !VALID_BITS == 0
+        && heavy & !arms == 0
+        && double & !arms == 0
+        && heavy & double == 0
+        && diagonal_only
+}
+
+const fn degrade_double(bits: u32) -> u32 {
+    let arms = bits & ARMS;
+    let double = (bits >> DOUBLE_SHIFT) & ARMS;
+    let without_double = bits & !(ARMS << DOUBLE_SHIFT);
+
+    let mut kept = double;
+    if arms & VERTICAL != double & VERTICAL {
+        kept &= !VERTICAL;
+    }
+    if arms & HORIZONTAL != double & HORIZONTAL {
+        kept &= !HORIZONTAL;
+    }
+
+    let degraded = without_double | (kept << DOUBLE_SHIFT);
+    if lookup_char(degraded).is_some() {
+        degraded
+    } else {
+        without_double
+    }
+}
+
+const fn lookup_bits(c: char) -> Option<u32> {
+    if c == ' ' {
+        return Some(0);
+    }
+
+    let offset = (c as u32).wrapping_sub(BOX_DRAWING_START) as usize;
+    if offset < BITS_BY_CHAR.len() {
+        BITS_BY_CHAR[offset]
+    } else {
+        None
+    }
+}
+
+const fn lookup_char(bits: u32) -> Option<char> {
+    let entry = match char_index(bits) {
+        Some(i) => CHAR_BY_BITS[i],
+        None => return None,
+    };
+
+    if entry == NO_CHAR {
+        None
+    } else {
+        Some(LINE_DRAWING_CHARS[entry as usize].0)
+    }
+}
+
+/// The number of solid, non-diagonal bit sets, indexed by their arm, heavy
+/// and double bits.
+const SOLID_SLOTS: usize = 1 << DASH_SHIFT;
+
+/// The number of dashed bit sets, indexed by their dash pattern and their arm
+/// and heavy bits.  Unicode has no dashed double lines.
+const DASHED_SLOTS: usize = 4 << DOUBLE_SHIFT;
+
+/// The position in [`LINE_DRAWING_CHARS`] of the glyph for every bit set,
+/// indexed by [`char_index`], or [`NO_CHAR`] if there is no glyph.
+const CHAR_BY_BITS: [u8; SOLID_SLOTS + DASHED_SLOTS + 4] = chars_by_bits();
+
+const NO_CHAR: u8 = u8::MAX;
+
+/// Return the index of `bits` in [`CHAR_BY_BITS`], or `None` if no glyph
+/// could have these bits.
+const fn char_index(bits: u32) -> Option<usize> {
+    if bits & !VALID_BITS != 0 {
+        None
+    } else if bits & DIAGONALS != 0 {
+        if bits & !DIAGONALS == 0 {
+            Some(SOLID_SLOTS + DASHED_SLOTS + (bits >> 14) as usize)
+        } else {
+            None
+        }
+    } else if bits & DASH_MASK == 0 {
+        Some(bits as usize)
+    } else if bits & (ARMS << DOUBLE_SHIFT) == 0 {
+        let dash = (bits & DASH_MASK) >> DASH_SHIFT;
+        let arms_and_heavy = bits & ((1 << DOUBLE_SHIFT) - 1);
+        Some(SOLID_SLOTS + ((dash << DOUBLE_SHIFT) | arms_and_heavy) as usize)
+    } else {
+        None
+    }
+}
+
+const fn chars_by_bits() -> [u8; SOLID_SLOTS + DASHED_SLOTS + 4] {
+    let mut table = [NO_CHAR; SOLID_SLOTS + DASHED_SLOTS + 4];
+
+    let mut i = 0;
+    while i < LINE_DRAWING_CHARS.len() {
+        let bits = LINE_DRAWING_CHARS[i].1;
+        let index = match char_index(bits) {
+            Some(index) => index,
+            None => panic!("every glyph must have a slot"),
+        };
+        // Keep the first glyph for each bit set, so square corners win over
+        // rounded ones.
+        if table[index] == NO_CHAR {
+            table[index] = i as u8;
+        }
+        i += 1;
+    }
+
+    table
+}
+
+/// The first code point of the Unicode Box Drawing block.
+const BOX_DRAWING_START: u32 = 0x2500;
+
+/// The bits for every char in the Box Drawing block, indexed by its offset
+/// from the start of the block.  Unsupported chars are `None`.
+const BITS_BY_CHAR: [Option<u32>; 128] = bits_by_char();
+
+const fn bits_by_char() -> [Option<u32>; 128] {
+    let mut table = [None; 128];
+
+    let mut i = 0;
+    while i < LINE_DRAWING_CHARS.len() {
+        let (c, bits) = LINE_DRAWING_CHARS[i];
+        let offset = (c as u32).wrapping_sub(BOX_DRAWING_START) as usize;
+        if offset < table.len() {
+            table[offset] = Some(bits);
+        }
+        i += 1;
     }
 
-    LINE_DRAWING_CHARS[bits as usize]
-}
-
-const LINE_DRAWING_CHARS: [char; 16] = [
-    ' ',        // 0000
-    '\u{2575}', // 0001
-    '\u{2576}', // 0010
-    '\u{2514}', // 0011
-    '\u{2577}', // 0100
-    '\u{2502}', // 0101
-    '\u{250c}', // 0110
-    '\u{251c}', // 0111
-    '\u{2574}', // 1000
-    '\u{2518}', // 1001
-    '\u{2500}', // 1010
-    '\u{2534}', // 1011
-    '\u{2510}', // 1100
-    '\u{2524}', // 1101
-    '\u{252c}', // 1110
-    '\u{253c}', // 1111
+    table
+}
+
+const LINE_DRAWING_CHARS: [(char, u32); 129] = [
+    (' ', 0b0000_0000),
+    // Light
+    ('\u{2575}', 0b0000_0001),
+    ('\u{2576}', 0b0000_0010),
+    ('\u{2514}', 0b0000_0011),
+    ('\u{2577}', 0b0000_0100),
+    ('\u{2502}', 0b0000_0101),
+    ('\u{250c}', 0b0000_0110),
+    ('\u{251c}', 0b0000_0111),
+    ('\u{2574}', 0b0000_1000),
+    ('\u{2518}', 0b0000_1001),
+    ('\u{2500}', 0b0000_1010),
+    ('\u{2534}', 0b0000_1011),
+    ('\u{2510}', 0b0000_1100),
+    ('\u{2524}', 0b0000_1101),
+    ('\u{252c}', 0b0000_1110),
+    ('\u{253c}', 0b0000_1111),
+    // Heavy
+    ('\u{2579}', 0b0001_0001),
+    ('\u{257a}', 0b0010_0010),
+    ('\u{2517}', 0b0011_0011),
+    ('\u{257b}', 0b0100_0100),
+    ('\u{2503}', 0b0101_0101),
+    ('\u{250f}', 0b0110_0110),
+    ('\u{2523}', 0b0111_0111),
+    ('\u{2578}', 0b1000_1000),
+    ('\u{251b}', 0b1001_1001),
+    ('\u{2501}', 0b1010_1010),
+    ('\u{253b}', 0b1011_1011),
+    ('\u{2513}', 0b1100_1100),
+    ('\u{252b}', 0b1101_1101),
+    ('\u{2533}', 0b1110_1110),
+    ('\u{254b}', 0b1111_1111),
+    // Double
+    ('\u{255a}', 0b0011_0000_0011),
+    ('\u{2551}', 0b0101_0000_0101),
+    ('\u{2554}', 0b0110_0000_0110),
+    ('\u{2560}', 0b0111_0000_0111),
+    ('\u{255d}', 0b1001_0000_1001),
+    ('\u{2550}', 0b1010_0000_1010),
+    ('\u{2569}', 0b1011_0000_1011),
+    ('\u{2557}', 0b1100_0000_1100),
+    ('\u{2563}', 0b1101_0000_1101),
+    ('\u{2566}', 0b1110_0000_1110),
+    ('\u{256c}', 0b1111_0000_1111),
+    // Mixed light and heavy
+    ('\u{250d}', 0b0010_0110),
+    ('\u{250e}', 0b0100_0110),
+    ('\u{2511}', 0b1000_1100),
+    ('\u{2512}', 0b0100_1100),
+    ('\u{2515}', 0b0010_0011),
+    ('\u{2516}', 0b0001_0011),
+    ('\u{2519}', 0b1000_1001),
+    ('\u{251a}', 0b0001_1001),
+    ('\u{251d}', 0b0010_0111),
+    ('\u{251e}', 0b0001_0111),
+    ('\u{251f}', 0b0100_0111),
+    ('\u{2520}', 0b0101_0111),
+    ('\u{2521}', 0b0011_0111),
+    ('\u{2522}', 0b0110_0111),
+    ('\u{2525}', 0b1000_1101),
+    ('\u{2526}', 0b0001_1101),
+    ('\u{2527}', 0b0100_1101),
+    ('\u{2528}', 0b0101_1101),
+    ('\u{2529}', 0b1001_1101),
+    ('\u{252a}', 0b1100_1101),
+    ('\u{252d}', 0b1000_1110),
+    ('\u{252e}', 0b0010_1110),
+    ('\u{252f}', 0b1010_1110),
+    ('\u{2530}', 0b0100_1110),
+    ('\u{2531}', 0b1100_1110),
+    ('\u{2532}', 0b0110_1110),
+    ('\u{2535}', 0b1000_1011),
+    ('\u{2536}', 0b0010_1011),
+    ('\u{2537}', 0b1010_1011),
+    ('\u{2538}', 0b0001_1011),
+    ('\u{2539}', 0b1001_1011),
+    ('\u{253a}', 0b0011_1011),
+    ('\u{253d}', 0b1000_1111),
+    ('\u{253e}', 0b0010_1111),
+    ('\u{253f}', 0b1010_1111),
+    ('\u{2540}', 0b0001_1111),
+    ('\u{2541}', 0b0100_1111),
+    ('\u{2542}', 0b0101_1111),
+    ('\u{2543}', 0b1001_1111),
+    ('\u{2544}', 0b0011_1111),
+    ('\u{2545}', 0b1100_1111),
+    ('\u{2546}', 0b0110_1111),
+    ('\u{2547}', 0b1011_1111),
+    ('\u{2548}', 0b1110_1111),
+    ('\u{2549}', 0b1101_1111),
+    ('\u{254a}', 0b0111_1111),
+    ('\u{257c}', 0b0010_1010),
+    ('\u{257d}', 0b0100_0101),
+    ('\u{257e}', 0b1000_1010),
+    ('\u{257f}', 0b0001_0101),
+    // Mixed single and double
+    ('\u{2552}', 0b0010_0000_0110),
+    ('\u{2553}', 0b0100_0000_0110),
+    ('\u{2555}', 0b1000_0000_1100),
+    ('\u{2556}', 0b0100_0000_1100),
+    ('\u{2558}', 0b0010_0000_0011),
+    ('\u{2559}', 0b0001_0000_0011),
+    ('\u{255b}', 0b1000_0000_1001),
+    ('\u{255c}', 0b0001_0000_1001),
+    ('\u{255e}', 0b0010_0000_0111),
+    ('\u{255f}', 0b0101_0000_0111),
+    ('\u{2561}', 0b1000_0000_1101),
+    ('\u{2562}', 0b0101_0000_1101),
+    ('\u{2564}', 0b1010_0000_1110),
+    ('\u{2565}', 0b0100_0000_1110),
+    ('\u{2567}', 0b1010_0000_1011),
+    ('\u{2568}', 0b0001_0000_1011),
+    ('\u{256a}', 0b1010_0000_1111),
+    ('\u{256b}', 0b0101_0000_1111),
+    // Dashed
+    ('\u{254c}', 0b01_0000_0000_1010),
+    ('\u{254d}', 0b01_0000_1010_1010),
+    ('\u{254e}', 0b01_0000_0000_0101),
+    ('\u{254f}', 0b01_0000_0101_0101),
+    ('\u{2504}', 0b10_0000_0000_1010),
+    ('\u{2505}', 0b10_0000_1010_1010),
+    ('\u{2506}', 0b10_0000_0000_0101),
+    ('\u{2507}', 0b10_0000_0101_0101),
+    ('\u{2508}', 0b11_0000_0000_1010),
+    ('\u{2509}', 0b11_0000_1010_1010),
+    ('\u{250a}', 0b11_0000_0000_0101),
+    ('\u{250b}', 0b11_0000_0101_0101),
+    // Diagonal
+    ('\u{2571}', 0b0100_0000_0000_0000),
+    ('\u{2572}', 0b1000_0000_0000_0000),
+    ('\u{2573}', 0b1100_0000_0000_0000),
+    // Rounded.  These come after the square corners so that those are the
+    // ones found when looking up by bits.
+    ('\u{256d}', 0b0000_0000_0110),
+    ('\u{256e}', 0b0000_0000_1100),
+    ('\u{256f}', 0b0000_0000_1001),
+    ('\u{2570}', 0b0000_0000_0011),
 ];
 
 #[cfg(test)]
@@ -114,9 +937,268 @@ mod tests {
         assert_eq!(Some(base_char), result);
     }
 
+    #[test]
+    fn lookup_char_matches_linear_scan() {
+        for bits in 0..=u16::MAX as u32 {
+            let expected = LINE_DRAWING_CHARS
+                .iter()
+                .find(|&&(_, b)| b == bits)
+                .map(|&(c, _)| c);
+
+            assert_eq!(expected, lookup_char(bits), "bits {:#b}", bits);
+        }
+    }
+
+    #[test]
+    fn stack_keeps_rounded_corner_with_empty() {
+        assert_eq!(Some('\u{256d}'), stack(' ', '\u{256d}'));
+        assert_eq!(Some('\u{256d}'), stack('\u{256d}', ' '));
+        assert_eq!(Some('\u{256f}'), stack('\u{256f}', '\u{2574}'));
+        assert_eq!(Some('\u{251c}'), stack('\u{2570}', '\u{256d}'));
+    }
+
     #[test]
     #[should_panic(expected = "but got 16")]
     fn bits_to_char_panics_on_input_16() {
         bits_to_char(16);
     }
+
+    #[test]
+    fn checked_bits_to_char_handles_every_bit_set() {
+        for bits in 0..(1 << 17) {
+            assert_eq!(is_valid(bits), checked_bits_to_char(bits).is_some());
+        }
+    }
+
+    #[test]
+    fn all_chars_round_trip() {
+        for &(c, bits) in LINE_DRAWING_CHARS.iter() {
+            assert_eq!(Some(bits as usize), char_to_bits(c));
+            assert_eq!(Some(bits as usize), char_to_bits(bits_to_char(bits)));
+        }
+    }
+
+    #[test]
+    fn stack_heavy() {
+        assert_eq!(Some('\u{254b}'), stack('\u{2501}', '\u{2503}'));
+        assert_eq!(Some('\u{2503}'), stack('\u{2579}', '\u{257b}'));
+        assert_eq!(Some('\u{2503}'), stack('\u{2503}', '\u{2502}'));
+    }
+
+    #[test]
+    fn stack_mixed_weights() {
+        assert_eq!(Some('\u{253f}'), stack('\u{2501}', '\u{2502}'));
+        assert_eq!(Some('\u{2521}'), stack('\u{2517}', '\u{2577}'));
+        assert_eq!(Some('\u{2546}'), stack('\u{250f}', '\u{2518}'));
+    }
+
+    #[test]
+    fn stack_half_weight_stubs() {
+        assert_eq!(Some('\u{257e}'), stack('\u{2578}', '\u{2576}'));
+        assert_eq!(Some('\u{257c}'), stack('\u{2574}', '\u{257a}'));
+        assert_eq!(Some('\u{257d}'), stack('\u{2575}', '\u{257b}'));
+        assert_eq!(Some('\u{257f}'), stack('\u{2577}', '\u{2579}'));
+        assert_eq!(Some('\u{2501}'), stack('\u{257c}', '\u{2578}'));
+    }
+
+    #[test]
+    fn every_light_and_heavy_combination_supported() {
+        for arms in 0..16 {
+            for heavy in 0..16 {
+                if heavy & !arms == 0 {
+                    assert!(lookup_char(heavy << HEAVY_SHIFT | arms).is_some());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lookup_outside_box_drawing_block() {
+        assert_eq!(None, lookup_bits('\u{24ff}'));
+        assert_eq!(None, lookup_bits('\u{2580}'));
+        assert_eq!(None, lookup_bits('\0'));
+        assert_eq!(Some(0), lookup_bits(' '));
+    }
+
+    #[test]
+    fn all_junctions_supported() {
+        for c in '\u{250c}'..='\u{254b}' {
+            let bits = char_to_bits(c).unwrap();
+            assert_eq!(c, bits_to_char(bits as u32));
+        }
+    }
+
+    #[test]
+    fn stack_double() {
+        assert_eq!(Some('\u{256c}'), stack('\u{2550}', '\u{2551}'));
+        assert_eq!(Some('\u{2569}'), stack('\u{255a}', '\u{255d}'));
+        assert_eq!(Some('\u{2551}'), stack('\u{2551}', '\u{2502}'));
+    }
+
+    #[test]
+    fn stack_mixed_single_double() {
+        assert_eq!(Some('\u{256a}'), stack('\u{2550}', '\u{2502}'));
+        assert_eq!(Some('\u{255f}'), stack('\u{2551}', '\u{2576}'));
+        assert_eq!(Some('\u{2562}'), stack('\u{2551}', '\u{2574}'));
+    }
+
+    #[test]
+    fn double_fallback() {
+        // Double up and single down: the vertical axis degrades to light
+        // while the double right arm is kept.
+        assert_eq!(Some('\u{255e}'), stack('\u{255a}', '\u{2502}'));
+        // Double left and single right: the horizontal axis degrades.
+        assert_eq!(Some('\u{2568}'), stack('\u{255d}', '\u{2576}'));
+        // Lone double arm: there are no double stubs.
+        assert_eq!('\u{2575}', bits_to_char(0b0001_0000_0001));
+        // Double and heavy never mix.
+        assert_eq!(Some('\u{251d}'), stack('\u{2551}', '\u{257a}'));
+    }
+
+    #[test]
+    fn rounded_corners_decode_as_square() {
+        assert_eq!(char_to_bits('\u{250c}'), char_to_bits('\u{256d}'));
+        assert_eq!(Some('\u{2534}'), stack('\u{2570}', '\u{256f}'));
+        assert_eq!('\u{2518}', bits_to_char(0b1001));
+    }
+
+    #[test]
+    fn stack_with_rounded_corners() {
+        let options = StackOptions {
+            corner_style: CornerStyle::Rounded,
+            ..StackOptions::default()
+        };
+
+        assert_eq!(
+            Some('\u{256f}'),
+            stack_with_options('\u{2575}', '\u{2574}', &options)
+        );
+        assert_eq!(
+            Some('\u{2513}'),
+            stack_with_options('\u{2578}', '\u{257b}', &options)
+        );
+        assert_eq!(
+            Some('\u{253c}'),
+            stack_with_options('\u{256d}', '\u{256f}', &options)
+        );
+    }
+
+    #[test]
+    fn stack_dashed() {
+        // Same pattern, or an empty cell, keeps the dashes.
+        assert_eq!(Some('\u{254e}'), stack('\u{254e}', '\u{254e}'));
+        assert_eq!(Some('\u{2509}'), stack(' ', '\u{2509}'));
+        assert_eq!(Some('\u{2507}'), stack('\u{2506}', '\u{2507}'));
+        // Different patterns, or a solid line, give a solid line.
+        assert_eq!(Some('\u{2500}'), stack('\u{2504}', '\u{2508}'));
+        assert_eq!(Some('\u{2501}'), stack('\u{254c}', '\u{2501}'));
+        assert_eq!(Some('\u{2500}'), stack('\u{2504}', '\u{2574}'));
+        // Junctions are never dashed.
+        assert_eq!(Some('\u{253f}'), stack('\u{2505}', '\u{250a}'));
+        assert_eq!(Some('\u{2524}'), stack('\u{254e}', '\u{2574}'));
+    }
+
+    #[test]
+    fn stack_diagonals() {
+        assert_eq!(Some('\u{2573}'), stack('\u{2571}', '\u{2572}'));
+        assert_eq!(Some('\u{2573}'), stack('\u{2573}', '\u{2571}'));
+        assert_eq!(Some('\u{2572}'), stack(' ', '\u{2572}'));
+        assert_eq!(None, stack('\u{2572}', '\u{2503}'));
+    }
+
+    #[test]
+    fn stack_with_policies() {
+        let border = '\u{2500}';
+
+        assert_eq!(Ok('a'), stack_with(border, 'a', StackPolicy::TopWins));
+        assert_eq!(Ok(border), stack_with(border, 'a', StackPolicy::BottomWins));
+        assert_eq!(Ok(border), stack_with('a', border, StackPolicy::LineWins));
+        assert_eq!(Ok('a'), stack_with('a', border, StackPolicy::TextWins));
+        assert_eq!(Ok('b'), stack_with('a', 'b', StackPolicy::LineWins));
+        assert_eq!(
+            Ok('\u{2571}'),
+            stack_with(border, '\u{2571}', StackPolicy::TextWins)
+        );
+        assert_eq!(
+            Err(StackError::UnsupportedOperand('a', Operand::Second)),
+            stack_with(border, 'a', StackPolicy::Error)
+        );
+    }
+
+    #[test]
+    fn stack_with_spaces_under_error_policy() {
+        assert_eq!(Ok('a'), stack_with(' ', 'a', StackPolicy::Error));
+        assert_eq!(Ok('a'), stack_with('a', ' ', StackPolicy::Error));
+    }
+
+    #[test]
+    fn stack_conflicting_weights() {
+        let options = |precedence| StackOptions {
+            precedence,
+            ..StackOptions::default()
+        };
+
+        // Heavy horizontal across a double frame junction.
+        assert_eq!(Some('\u{2501}'), stack('\u{2550}', '\u{2501}'));
+        assert_eq!(
+            Some('\u{2566}'),
+            stack_with_options('\u{2501}', '\u{2566}', &options(Precedence::TopWins))
+        );
+        assert_eq!(
+            Some('\u{252f}'),
+            stack_with_options('\u{2501}', '\u{2566}', &options(Precedence::BottomWins))
+        );
+        assert_eq!(
+            Err(StackError::Incompatible('\u{2501}', '\u{2550}')),
+            try_stack_with_options('\u{2501}', '\u{2550}', &options(Precedence::Error))
+        );
+        // Only shared arms can conflict.
+        assert_eq!(
+            Ok('\u{253f}'),
+            try_stack_with_options('\u{2501}', '\u{2502}', &options(Precedence::Error))
+        );
+    }
+
+    #[test]
+    fn unstack_keeps_remaining_styles() {
+        assert_eq!(Some('\u{2550}'), unstack('\u{256c}', '\u{2502}'));
+        assert_eq!(Some('\u{257a}'), unstack('\u{2517}', '\u{2579}'));
+        assert_eq!(Some('\u{2505}'), unstack('\u{2505}', '\u{2502}'));
+        assert_eq!(Some('\u{256d}'), unstack('\u{256d}', ' '));
+        assert_eq!(Some('\u{2576}'), unstack('\u{256d}', '\u{2577}'));
+    }
+
+    #[test]
+    fn unstack_is_inverse_of_stack_for_disjoint_arms() {
+        for a in '\u{2500}'..='\u{257f}' {
+            for b in '\u{2500}'..='\u{257f}' {
+                let (bits_a, bits_b) = match (char_to_bits(a), char_to_bits(b)) {
+                    (Some(bits_a), Some(bits_b)) => (bits_a as u32, bits_b as u32),
+                    _ => continue,
+                };
+                if bits_a & bits_b & (ARMS | DIAGONALS) != 0 || bits_a & DASH_MASK != 0 {
+                    continue;
+                }
+                // Only stacks with an exact glyph keep enough to undo.
+                let stacked = match stack(a, b) {
+                    Some(stacked) if char_to_bits(stacked) == Some((bits_a | bits_b) as usize) => {
+                        stacked
+                    }
+                    _ => continue,
+                };
+                if bits_b != 0 {
+                    assert_eq!(Some(bits_to_char(bits_a)), unstack(stacked, b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stack_unsupported_first_char() {
+        assert_eq!(None, stack('x', '\u{2502}'));
+        assert_eq!(
+            Err(StackError::UnsupportedOperand('x', Operand::First)),
+            try_stack('x', 'y')
+        );
+    }
 }
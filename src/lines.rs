@@ -0,0 +1,374 @@
+//! The [`Lines`] type and the styles of its arms.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+use crate::{
+    is_valid, lookup_bits, resolve_char, stack_bits, unstack_bits, Directions, Precedence,
+    DASH_MASK, DASH_SHIFT, DIAGONALS, DOUBLE_SHIFT, FALLING_DIAGONAL, HEAVY_SHIFT, RISING_DIAGONAL,
+};
+
+/// The weight of an arm of a line-drawing char.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Weight {
+    /// A light (single) line, as in `─`.
+    Light,
+    /// A heavy line, as in `━`.
+    Heavy,
+    /// A double line, as in `═`.
+    Double,
+}
+
+/// The dash pattern of a straight line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Dash {
+    /// A solid line, as in `─`.
+    #[default]
+    Solid,
+    /// A double dash, as in `╌`.
+    Double,
+    /// A triple dash, as in `┄`.
+    Triple,
+    /// A quadruple dash, as in `┈`.
+    Quadruple,
+}
+
+/// A line-drawing char, described by the weight of each of its arms, its dash
+/// pattern and its diagonals.
+///
+/// This is a typed wrapper around the crate's bit set format, which is
+/// described in [`bits_to_char`](crate::bits_to_char).  Every `Lines` is a
+/// valid bit set, so it can always be converted to a char, falling back to
+/// the closest glyph as described there.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use unicode_line_stacker::{Directions, Lines, Weight};
+///
+/// let lines = Lines::try_from('┿').unwrap();
+/// assert_eq!(Directions::ALL, lines.directions());
+/// assert_eq!(Some(Weight::Heavy), lines.weight(Directions::HORIZONTAL));
+/// assert_eq!(Some(Weight::Light), lines.weight(Directions::UP));
+///
+/// let tee = Lines::new(Directions::VERTICAL, Weight::Double)
+///     .stack(Lines::new(Directions::RIGHT, Weight::Double))
+///     .unwrap();
+/// assert_eq!('╠', char::from(tee));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Lines(u32);
+
+impl Lines {
+    /// No lines at all, as in a space.
+    pub const EMPTY: Lines = Lines(0);
+    /// The diagonal `╱`.
+    pub const RISING_DIAGONAL: Lines = Lines(RISING_DIAGONAL);
+    /// The diagonal `╲`.
+    pub const FALLING_DIAGONAL: Lines = Lines(FALLING_DIAGONAL);
+
+    /// Create a `Lines` from a bit set, or return `None` if the bit set is
+    /// invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unicode_line_stacker::Lines;
+    ///
+    /// assert!(Lines::from_bits(0b1011_1011).is_some());
+    /// // Heavy up without an up arm.
+    /// assert_eq!(None, Lines::from_bits(0b0001_0000));
+    /// ```
+    #[inline]
+    pub const fn from_bits(bits: u32) -> Option<Lines> {
+        if is_valid(bits) {
+            Some(Lines(bits))
+        } else {
+            None
+        }
+    }
+
+    /// Return the bit set for these lines.
+    #[inline]
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Create solid lines with arms in `directions`, all of the same weight.
+    #[inline]
+    pub const fn new(directions: Directions, weight: Weight) -> Lines {
+        let arms = directions.bits() as u32;
+
+        Lines(match weight {
+            Weight::Light => arms,
+            Weight::Heavy => arms | (arms << HEAVY_SHIFT),
+            Weight::Double => arms | (arms << DOUBLE_SHIFT),
+        })
+    }
+
+    /// Return the directions of the horizontal and vertical arms.
+    #[inline]
+    pub const fn directions(self) -> Directions {
+        Directions::from_bits_truncate(self.0 as u8)
+    }
+
+    /// Return the weight shared by the arms in `directions`, or `None` if
+    /// `directions` is empty, any of its arms are missing, or the arms have
+    /// different weights.
+    pub fn weight(self, directions: Directions) -> Option<Weight> {
+        let arms = directions.bits() as u32;
+        let heavy = (self.0 >> HEAVY_SHIFT) & arms;
+        let double = (self.0 >> DOUBLE_SHIFT) & arms;
+
+        if directions.is_empty() || !self.directions().contains(directions) {
+            None
+        } else if heavy == arms {
+            Some(Weight::Heavy)
+        } else if double == arms {
+            Some(Weight::Double)
+        } else if heavy == 0 && double == 0 {
+            Some(Weight::Light)
+        } else {
+            None
+        }
+    }
+
+    /// Return the dash pattern.
+    #[inline]
+    pub const fn dash(self) -> Dash {
+        match (self.0 & DASH_MASK) >> DASH_SHIFT {
+            0 => Dash::Solid,
+            1 => Dash::Double,
+            2 => Dash::Triple,
+            _ => Dash::Quadruple,
+        }
+    }
+
+    /// Return a copy of these lines with the dash pattern replaced.
+    ///
+    /// Only straight light or heavy lines can be drawn dashed; any other
+    /// lines are drawn solid regardless of the dash pattern.  Diagonals
+    /// cannot have a dash pattern at all, so they are returned unchanged.
+    #[inline]
+    pub const fn with_dash(self, dash: Dash) -> Lines {
+        if self.has_diagonals() {
+            return self;
+        }
+
+        let dash = match dash {
+            Dash::Solid => 0,
+            Dash::Double => 1,
+            Dash::Triple => 2,
+            Dash::Quadruple => 3,
+        };
+
+        Lines((self.0 & !DASH_MASK) | (dash << DASH_SHIFT))
+    }
+
+    /// Return whether these lines are made of diagonals.
+    #[inline]
+    pub const fn has_diagonals(self) -> bool {
+        self.0 & DIAGONALS != 0
+    }
+
+    /// Stack `other` on top of these lines, as [`stack`](crate::stack)
+    /// does for chars.
+    ///
+    /// Returns `None` if the result cannot be drawn at all.
+    #[inline]
+    pub const fn stack(self, other: Lines) -> Option<Lines> {
+        self.stack_with_precedence(other, Precedence::Heaviest)
+    }
+
+    /// Remove the arms and diagonals of `other` from these lines, as
+    /// [`unstack`](crate::unstack) does for chars.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unicode_line_stacker::{Directions, Lines, Weight};
+    ///
+    /// let cross = Lines::new(Directions::ALL, Weight::Heavy);
+    /// let rest = cross.unstack(Directions::VERTICAL.into());
+    /// assert_eq!(Lines::new(Directions::HORIZONTAL, Weight::Heavy), rest);
+    /// ```
+    #[inline]
+    pub const fn unstack(self, other: Lines) -> Lines {
+        Lines(unstack_bits(self.0, other.0))
+    }
+
+    /// Rotate these lines a quarter turn clockwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    /// use unicode_line_stacker::Lines;
+    ///
+    /// let lines = Lines::try_from('┞').unwrap();
+    /// assert_eq!('┮', char::from(lines.rotate_cw()));
+    /// ```
+    #[inline]
+    pub fn rotate_cw(self) -> Lines {
+        self.map_directions(Directions::rotate_cw, true)
+    }
+
+    /// Rotate these lines a quarter turn counterclockwise.
+    #[inline]
+    pub fn rotate_ccw(self) -> Lines {
+        self.map_directions(Directions::rotate_ccw, true)
+    }
+
+    /// Rotate these lines a half turn.
+    #[inline]
+    pub fn rotate_180(self) -> Lines {
+        self.map_directions(Directions::rotate_180, false)
+    }
+
+    /// Mirror these lines left to right.
+    #[inline]
+    pub fn flip_horizontal(self) -> Lines {
+        self.map_directions(Directions::flip_horizontal, true)
+    }
+
+    /// Mirror these lines top to bottom.
+    #[inline]
+    pub fn flip_vertical(self) -> Lines {
+        self.map_directions(Directions::flip_vertical, true)
+    }
+
+    /// Apply `f` to the arms and their weights, and swap the diagonals if
+    /// `swap_diagonals` is set.
+    fn map_directions(self, f: impl Fn(Directions) -> Directions, swap_diagonals: bool) -> Lines {
+        let map = |shift: u32| {
+            let directions = Directions::from_bits_truncate((self.0 >> shift) as u8);
+            (f(directions).bits() as u32) << shift
+        };
+
+        let diagonals = match self.0 & DIAGONALS {
+            RISING_DIAGONAL if swap_diagonals => FALLING_DIAGONAL,
+            FALLING_DIAGONAL if swap_diagonals => RISING_DIAGONAL,
+            diagonals => diagonals,
+        };
+
+        Lines(map(0) | map(HEAVY_SHIFT) | map(DOUBLE_SHIFT) | (self.0 & DASH_MASK) | diagonals)
+    }
+
+    /// Like [`Lines::stack`], but with `precedence` deciding the weight of
+    /// arms present in both.
+    ///
+    /// Returns `None` if the result cannot be drawn at all, or if the weights
+    /// conflict and `precedence` is [`Precedence::Error`].
+    #[inline]
+    pub const fn stack_with_precedence(
+        self,
+        other: Lines,
+        precedence: Precedence,
+    ) -> Option<Lines> {
+        match stack_bits(self.0, other.0, precedence) {
+            Some(bits) => Lines::from_bits(bits),
+            None => None,
+        }
+    }
+}
+
+/// Convert a set of directions to light lines.
+impl From<Directions> for Lines {
+    #[inline]
+    fn from(directions: Directions) -> Lines {
+        Lines::new(directions, Weight::Light)
+    }
+}
+
+/// Convert a line-drawing char to lines.
+///
+/// Fails if the char is unsupported.
+impl TryFrom<char> for Lines {
+    type Error = TryFromCharError;
+
+    #[inline]
+    fn try_from(c: char) -> Result<Lines, TryFromCharError> {
+        lookup_bits(c).map(Lines).ok_or(TryFromCharError(c))
+    }
+}
+
+/// Convert lines to the closest line-drawing char.
+impl From<Lines> for char {
+    #[inline]
+    fn from(lines: Lines) -> char {
+        resolve_char(lines.0).expect("every valid bit set has a glyph")
+    }
+}
+
+/// The error returned when converting an unsupported char.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TryFromCharError(pub(crate) char);
+
+impl TryFromCharError {
+    /// Return the char that could not be converted.
+    #[inline]
+    pub fn unsupported_char(&self) -> char {
+        self.0
+    }
+}
+
+impl fmt::Display for TryFromCharError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unsupported line-drawing char {:?}", self.0)
+    }
+}
+
+impl Error for TryFromCharError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagonals_have_no_dash() {
+        let dashed = Lines::RISING_DIAGONAL.with_dash(Dash::Triple);
+
+        assert_eq!(Lines::RISING_DIAGONAL, dashed);
+        assert_eq!(Dash::Solid, dashed.dash());
+        assert_eq!(Some(dashed), dashed.stack(Lines::EMPTY));
+    }
+
+    #[test]
+    fn weights() {
+        let lines = Lines::try_from('\u{2541}').unwrap(); // down heavy and up horizontal light
+
+        assert_eq!(Some(Weight::Heavy), lines.weight(Directions::DOWN));
+        assert_eq!(Some(Weight::Light), lines.weight(!Directions::DOWN));
+        assert_eq!(None, lines.weight(Directions::VERTICAL));
+        assert_eq!(None, lines.weight(Directions::NONE));
+        assert_eq!(None, Lines::EMPTY.weight(Directions::UP));
+    }
+
+    #[test]
+    fn dashes() {
+        let lines = Lines::try_from('\u{250a}').unwrap();
+
+        assert_eq!(Dash::Quadruple, lines.dash());
+        assert_eq!('\u{2502}', char::from(lines.with_dash(Dash::Solid)));
+        assert_eq!('\u{254e}', char::from(lines.with_dash(Dash::Double)));
+    }
+
+    #[test]
+    fn stack_diagonals() {
+        let cross = Lines::RISING_DIAGONAL.stack(Lines::FALLING_DIAGONAL);
+
+        assert_eq!(Some('\u{2573}'), cross.map(char::from));
+        assert_eq!(Directions::NONE, cross.unwrap().directions());
+        assert_eq!(None, cross.unwrap().stack(Directions::UP.into()));
+    }
+
+    #[test]
+    fn try_from_unsupported_char() {
+        let err = Lines::try_from('a').unwrap_err();
+
+        assert_eq!('a', err.unsupported_char());
+        assert_eq!("unsupported line-drawing char 'a'", err.to_string());
+    }
+}
@@ -0,0 +1,179 @@
+//! Drawing lines that have no exact glyph.
+
+use crate::{
+    lookup_bits, round_corner, CornerStyle, Directions, Lines, ARMS, DASH_MASK, DOUBLE_SHIFT,
+    HEAVY_SHIFT,
+};
+
+/// The result of [`nearest_char`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NearestChar {
+    /// The closest available glyph.
+    pub ch: char,
+    /// The arms that are drawn differently from how they were described.
+    pub degraded: Directions,
+}
+
+/// Return the glyph closest to `lines`, drawing light corners in
+/// `corner_style`, and report which arms could not be drawn as described.
+///
+/// Styles Unicode has no glyph for are degraded in this order:
+///
+/// 1. Double arms are drawn light, first on each axis (vertical and
+///    horizontal) whose arms are not all double, then everywhere.
+/// 2. Heavy arms are drawn light.
+/// 3. Dashed lines are drawn solid.
+/// 4. Rounded corners are drawn square.
+///
+/// Unicode has a glyph for every solid combination of light and heavy arms,
+/// so heavy arms are only drawn light to keep a dash.  If the dash cannot be
+/// kept either, the line is drawn solid with its heavy arms instead.  A dash
+/// pattern applies to the whole line, so if it is dropped, every arm counts
+/// as degraded.  Rounding only applies to corners, so T-junctions and crosses
+/// drawn square are not counted as degraded.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::{nearest_char, CornerStyle, Directions, Lines, Weight};
+///
+/// // Double up with heavy right.
+/// let lines = Lines::new(Directions::UP, Weight::Double)
+///     .stack(Lines::new(Directions::RIGHT, Weight::Heavy))
+///     .unwrap();
+/// let nearest = nearest_char(lines, CornerStyle::Square);
+/// assert_eq!('┕', nearest.ch);
+/// assert_eq!(Directions::UP, nearest.degraded);
+///
+/// // Heavy corners cannot be rounded.
+/// let lines = Lines::new(Directions::DOWN | Directions::RIGHT, Weight::Heavy);
+/// let nearest = nearest_char(lines, CornerStyle::Rounded);
+/// assert_eq!('┏', nearest.ch);
+/// assert_eq!(Directions::DOWN | Directions::RIGHT, nearest.degraded);
+/// ```
+pub fn nearest_char(lines: Lines, corner_style: CornerStyle) -> NearestChar {
+    let requested = lines.bits();
+    let square = char::from(lines);
+    let actual = lookup_bits(square).expect("glyphs always have bits");
+
+    let changed = requested ^ actual;
+    let mut degraded = ((changed >> HEAVY_SHIFT) | (changed >> DOUBLE_SHIFT)) & ARMS;
+    if changed & DASH_MASK != 0 {
+        degraded |= requested & ARMS;
+    }
+
+    let ch = match corner_style {
+        CornerStyle::Square => square,
+        CornerStyle::Rounded => {
+            let rounded = round_corner(square);
+            if rounded == square && is_corner(lines.directions()) {
+                degraded |= requested & ARMS;
+            }
+            rounded
+        }
+    };
+
+    NearestChar {
+        ch,
+        degraded: Directions::from_bits_truncate(degraded as u8),
+    }
+}
+
+fn is_corner(directions: Directions) -> bool {
+    directions.len() == 2
+        && directions != Directions::VERTICAL
+        && directions != Directions::HORIZONTAL
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dash, Weight};
+
+    #[test]
+    fn exact_glyph_is_not_degraded() {
+        let lines = Lines::new(Directions::ALL, Weight::Double);
+
+        assert_eq!(
+            NearestChar {
+                ch: '\u{256c}',
+                degraded: Directions::NONE,
+            },
+            nearest_char(lines, CornerStyle::Rounded)
+        );
+    }
+
+    #[test]
+    fn dropped_dash_degrades_every_arm() {
+        let lines = Lines::new(Directions::HORIZONTAL | Directions::DOWN, Weight::Light)
+            .with_dash(Dash::Triple);
+        let nearest = nearest_char(lines, CornerStyle::Square);
+
+        assert_eq!('\u{252c}', nearest.ch);
+        assert_eq!(Directions::HORIZONTAL | Directions::DOWN, nearest.degraded);
+    }
+
+    #[test]
+    fn dashed_double_line_keeps_dash() {
+        let lines = Lines::new(Directions::HORIZONTAL, Weight::Double).with_dash(Dash::Triple);
+        let nearest = nearest_char(lines, CornerStyle::Square);
+
+        assert_eq!('\u{2504}', nearest.ch);
+        assert_eq!(Directions::HORIZONTAL, nearest.degraded);
+    }
+
+    #[test]
+    fn dashed_mixed_line_drops_heavy_arm() {
+        let lines = Lines::new(Directions::UP, Weight::Heavy)
+            .stack(Lines::new(Directions::DOWN, Weight::Light))
+            .unwrap()
+            .with_dash(Dash::Quadruple);
+        let nearest = nearest_char(lines, CornerStyle::Square);
+
+        assert_eq!('\u{250a}', nearest.ch);
+        assert_eq!(Directions::UP, nearest.degraded);
+    }
+
+    #[test]
+    fn dashed_heavy_corner_stays_heavy() {
+        let lines =
+            Lines::new(Directions::DOWN | Directions::RIGHT, Weight::Heavy).with_dash(Dash::Double);
+        let nearest = nearest_char(lines, CornerStyle::Square);
+
+        assert_eq!('\u{250f}', nearest.ch);
+        assert_eq!(Directions::DOWN | Directions::RIGHT, nearest.degraded);
+    }
+
+    #[test]
+    fn mixed_single_double_axis_degrades_only_double_arms() {
+        let lines = Lines::new(Directions::UP, Weight::Double)
+            .stack(Lines::new(
+                Directions::DOWN | Directions::HORIZONTAL,
+                Weight::Light,
+            ))
+            .unwrap();
+        let nearest = nearest_char(lines, CornerStyle::Square);
+
+        assert_eq!('\u{253c}', nearest.ch);
+        assert_eq!(Directions::UP, nearest.degraded);
+    }
+
+    #[test]
+    fn light_corner_is_rounded() {
+        let lines = Lines::new(Directions::UP | Directions::LEFT, Weight::Light);
+        let nearest = nearest_char(lines, CornerStyle::Rounded);
+
+        assert_eq!('\u{256f}', nearest.ch);
+        assert_eq!(Directions::NONE, nearest.degraded);
+    }
+
+    #[test]
+    fn junctions_are_not_degraded_by_rounding() {
+        let lines = Lines::new(Directions::ALL, Weight::Light);
+
+        assert_eq!(
+            Directions::NONE,
+            nearest_char(lines, CornerStyle::Rounded).degraded
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! Rotating and mirroring line-drawing chars.
+
+use std::convert::TryFrom;
+
+use crate::{is_rounded, round_corner, Lines};
+
+/// Rotate a line-drawing char a quarter turn clockwise.
+///
+/// Rounded corners stay rounded, dashed lines stay dashed, and the diagonals
+/// `╱` and `╲` swap.
+///
+/// Returns `None` if the char is unsupported.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::rotate_cw;
+///
+/// assert_eq!(Some('├'), rotate_cw('┴'));
+/// assert_eq!(Some('╮'), rotate_cw('╭'));
+/// assert_eq!(Some('┆'), rotate_cw('┄'));
+/// assert_eq!(Some('╲'), rotate_cw('╱'));
+/// ```
+pub fn rotate_cw(c: char) -> Option<char> {
+    transform(c, Lines::rotate_cw)
+}
+
+/// Rotate a line-drawing char a quarter turn counterclockwise.
+///
+/// Returns `None` if the char is unsupported.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(Some('╦'), unicode_line_stacker::rotate_ccw('╣'));
+/// ```
+pub fn rotate_ccw(c: char) -> Option<char> {
+    transform(c, Lines::rotate_ccw)
+}
+
+/// Rotate a line-drawing char a half turn.
+///
+/// Returns `None` if the char is unsupported.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(Some('┛'), unicode_line_stacker::rotate_180('┏'));
+/// ```
+pub fn rotate_180(c: char) -> Option<char> {
+    transform(c, Lines::rotate_180)
+}
+
+/// Mirror a line-drawing char left to right.
+///
+/// Returns `None` if the char is unsupported.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(Some('┥'), unicode_line_stacker::flip_horizontal('┝'));
+/// assert_eq!(Some('╲'), unicode_line_stacker::flip_horizontal('╱'));
+/// ```
+pub fn flip_horizontal(c: char) -> Option<char> {
+    transform(c, Lines::flip_horizontal)
+}
+
+/// Mirror a line-drawing char top to bottom.
+///
+/// Returns `None` if the char is unsupported.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(Some('╰'), unicode_line_stacker::flip_vertical('╭'));
+/// ```
+pub fn flip_vertical(c: char) -> Option<char> {
+    transform(c, Lines::flip_vertical)
+}
+
+fn transform(c: char, f: impl Fn(Lines) -> Lines) -> Option<char> {
+    let lines = Lines::try_from(c).ok()?;
+    let result = char::from(f(lines));
+
+    Some(if is_rounded(c) {
+        round_corner(result)
+    } else {
+        result
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_char_survives_a_full_turn() {
+        for c in std::iter::once(' ').chain('\u{2500}'..='\u{257f}') {
+            let turned = rotate_cw(c)
+                .and_then(rotate_cw)
+                .and_then(rotate_cw)
+                .and_then(rotate_cw);
+
+            assert_eq!(Some(c), turned);
+            assert_eq!(Some(c), rotate_ccw(c).and_then(rotate_cw));
+            assert_eq!(Some(c), flip_horizontal(c).and_then(flip_horizontal));
+            assert_eq!(rotate_180(c), flip_horizontal(c).and_then(flip_vertical));
+        }
+    }
+
+    #[test]
+    fn mixed_weights_follow_their_arms() {
+        assert_eq!(Some('\u{2527}'), flip_horizontal('\u{251f}'));
+        assert_eq!(Some('\u{2526}'), flip_horizontal('\u{251e}'));
+        assert_eq!(Some('\u{2561}'), flip_horizontal('\u{255e}'));
+        assert_eq!(Some('\u{2564}'), rotate_cw('\u{255f}'));
+    }
+
+    #[test]
+    fn unsupported_char() {
+        assert_eq!(None, rotate_cw('x'));
+    }
+}
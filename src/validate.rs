@@ -0,0 +1,242 @@
+//! Checking that the lines on a canvas connect up.
+
+use std::fmt;
+
+use crate::{Canvas, Directions};
+
+/// A problem found by [`validate`] in the cell at `(x, y)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    /// The column of the cell.
+    pub x: usize,
+    /// The row of the cell.
+    pub y: usize,
+    /// What is wrong with the cell.
+    pub kind: DiagnosticKind,
+}
+
+/// What is wrong with a cell reported by [`validate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DiagnosticKind {
+    /// The arm in `direction` points at `neighbour`, which has no arm
+    /// pointing back.
+    DanglingArm {
+        /// The direction of the arm.
+        direction: Directions,
+        /// The char the arm points at.
+        neighbour: char,
+    },
+    /// The arm in `direction` points off the edge of the canvas.
+    OffEdge {
+        /// The direction of the arm.
+        direction: Directions,
+    },
+    /// The cell has a single arm, in `direction`, and it points at a
+    /// neighbour with no arm pointing back.
+    IsolatedStub {
+        /// The direction of the arm.
+        direction: Directions,
+    },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}): ", self.x, self.y)?;
+        match self.kind {
+            DiagnosticKind::DanglingArm {
+                direction,
+                neighbour,
+            } => write!(
+                f,
+                "arm pointing {} meets {:?} with no arm pointing back",
+                name(direction),
+                neighbour
+            ),
+            DiagnosticKind::OffEdge { direction } => {
+                write!(f, "arm pointing {} runs off the edge", name(direction))
+            }
+            DiagnosticKind::IsolatedStub { direction } => {
+                write!(f, "isolated stub pointing {}", name(direction))
+            }
+        }
+    }
+}
+
+/// Check that every arm on the canvas points at a neighbour with an arm
+/// pointing back, and return a diagnostic for each one that does not.
+///
+/// Only cells with horizontal or vertical arms are checked.  Spaces, text
+/// and diagonals never have an arm pointing back.  A cell with a single arm
+/// that meets a neighbour without an arm pointing back, like a `╶` followed
+/// by a space, is reported as an
+/// [`IsolatedStub`](DiagnosticKind::IsolatedStub) rather than as a dangling
+/// arm.  An arm that runs off the edge is always reported as
+/// [`OffEdge`](DiagnosticKind::OffEdge), even on a single-arm cell.
+///
+/// Diagnostics are returned row by row, and within a cell clockwise from up.
+///
+/// # Examples
+///
+/// ```
+/// use unicode_line_stacker::{validate, Canvas, Diagnostic, DiagnosticKind, Directions};
+///
+/// let canvas = Canvas::from("┌─┐\n└─┘");
+/// assert!(validate(&canvas).is_empty());
+///
+/// let canvas = Canvas::from("┌─a\n└─┘");
+/// let diagnostics = validate(&canvas);
+///
+/// assert_eq!(
+///     vec![
+///         Diagnostic {
+///             x: 1,
+///             y: 0,
+///             kind: DiagnosticKind::DanglingArm {
+///                 direction: Directions::RIGHT,
+///                 neighbour: 'a',
+///             },
+///         },
+///         Diagnostic {
+///             x: 2,
+///             y: 1,
+///             kind: DiagnosticKind::DanglingArm {
+///                 direction: Directions::UP,
+///                 neighbour: 'a',
+///             },
+///         },
+///     ],
+///     diagnostics
+/// );
+/// assert_eq!(
+///     "(1, 0): arm pointing right meets 'a' with no arm pointing back",
+///     diagnostics[0].to_string()
+/// );
+/// ```
+pub fn validate(canvas: &Canvas) -> Vec<Diagnostic> {
+    let cells = canvas.orthogonal_lines();
+    let mut diagnostics = Vec::new();
+
+    for y in 0..canvas.height() {
+        for x in 0..canvas.width() {
+            let directions = match cells[y * canvas.width() + x] {
+                Some(lines) => lines.directions(),
+                None => continue,
+            };
+
+            let mut kinds = Vec::new();
+            for direction in directions - canvas.connected_arms(&cells, x, y) {
+                kinds.push(match canvas.neighbour(x, y, direction) {
+                    Some((nx, ny)) => DiagnosticKind::DanglingArm {
+                        direction,
+                        neighbour: canvas.get(nx, ny).expect("neighbour is on the canvas"),
+                    },
+                    None => DiagnosticKind::OffEdge { direction },
+                });
+            }
+
+            if let [DiagnosticKind::DanglingArm { direction, .. }] = kinds[..] {
+                if directions.len() == 1 {
+                    kinds[0] = DiagnosticKind::IsolatedStub { direction };
+                }
+            }
+
+            diagnostics.extend(kinds.into_iter().map(|kind| Diagnostic { x, y, kind }));
+        }
+    }
+
+    diagnostics
+}
+
+fn name(direction: Directions) -> &'static str {
+    match direction {
+        Directions::UP => "up",
+        Directions::RIGHT => "right",
+        Directions::DOWN => "down",
+        Directions::LEFT => "left",
+        _ => "in several directions",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(s: &str) -> Vec<DiagnosticKind> {
+        validate(&Canvas::from(s))
+            .into_iter()
+            .map(|diagnostic| diagnostic.kind)
+            .collect()
+    }
+
+    #[test]
+    fn arms_off_the_edge() {
+        assert_eq!(
+            vec![
+                DiagnosticKind::OffEdge {
+                    direction: Directions::UP
+                },
+                DiagnosticKind::OffEdge {
+                    direction: Directions::DOWN
+                },
+            ],
+            kinds("\u{2502}")
+        );
+    }
+
+    #[test]
+    fn isolated_stubs() {
+        assert_eq!(
+            vec![
+                DiagnosticKind::IsolatedStub {
+                    direction: Directions::RIGHT
+                },
+                DiagnosticKind::IsolatedStub {
+                    direction: Directions::LEFT
+                },
+            ],
+            kinds("\u{257a} \u{2574}")
+        );
+    }
+
+    #[test]
+    fn stub_off_the_edge_is_reported_as_off_edge() {
+        assert_eq!(
+            vec![DiagnosticKind::OffEdge {
+                direction: Directions::LEFT
+            }],
+            kinds("\u{2574}")
+        );
+    }
+
+    #[test]
+    fn connected_stubs_are_fine() {
+        assert!(kinds("\u{2576}\u{2574}").is_empty());
+    }
+
+    #[test]
+    fn diagonals_and_text_are_not_checked() {
+        assert!(kinds("\u{2571}a\u{2573}").is_empty());
+    }
+
+    #[test]
+    fn mismatched_weights_still_connect() {
+        assert!(kinds("\u{2576}\u{2578}").is_empty());
+        assert!(kinds("\u{2577}\n\u{2579}").is_empty());
+    }
+
+    #[test]
+    fn display_off_edge() {
+        let diagnostic = Diagnostic {
+            x: 3,
+            y: 0,
+            kind: DiagnosticKind::OffEdge {
+                direction: Directions::UP,
+            },
+        };
+
+        assert_eq!(
+            "(3, 0): arm pointing up runs off the edge",
+            diagnostic.to_string()
+        );
+    }
+}